@@ -13,7 +13,8 @@ struct FillMaterial {
     color: vec4<f32>,
     displacement: f32,
     shininess: f32,
-    specular_strength: f32
+    specular_strength: f32,
+    intensity: f32,
 };
 
 @group(2) @binding(0)
@@ -162,7 +163,7 @@ fn fragment(
     let lighting_color = material.color.rgb * (diffuse_color + 0.2) + specular_color;
     
     // Multiply with vertex color
-    let final_color = lighting_color * mesh.color.rgb;
+    let final_color = lighting_color * mesh.color.rgb * material.intensity;
     
     return vec4<f32>(final_color, material.color.a * mesh.color.a);
     // return vec4<f32>(camera_position, material.color.a);
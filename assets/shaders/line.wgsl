@@ -48,21 +48,241 @@
     mesh_functions,
     skinning,
     morph::morph,
-    forward_io::{Vertex, VertexOutput},
+    mesh_view_bindings::{view, globals},
     view_transformations::position_world_to_clip,
 }
 
+// Number of `EdgeClass` variants
+const EDGE_CLASS_COUNT: u32 = 6u;
+
 struct LineMaterial{
     color: vec4<f32>,
     displacement: f32,
+    use_vertex_color: i32,
+    silhouette_mode: i32,
+    silhouette_fade: f32,
+    width: f32,
+    width_in_world_units: i32,
+    line_cap: i32,
+    dash_pattern: i32,
+    dash_length: f32,
+    gap_length: f32,
+    dash_offset: f32,
+    dash_speed: f32,
+    fog_near: f32,
+    fog_far: f32,
+    fog_color: vec4<f32>,
+    reveal_progress: f32,
+    reveal_spread: f32,
+    intensity: f32,
+    edge_class_colors: array<vec4<f32>, EDGE_CLASS_COUNT>,
 }
 
+const SILHOUETTE_OFF: i32 = 0;
+const SILHOUETTE_ONLY: i32 = 1;
+const SILHOUETTE_FADE: i32 = 2;
+
+const LINE_CAP_BUTT: i32 = 0;
+const LINE_CAP_SQUARE: i32 = 1;
+const LINE_CAP_ROUND: i32 = 2;
+
+const DASH_SOLID: i32 = 0;
+const DASH_DASHED: i32 = 1;
+const DASH_DOTTED: i32 = 2;
+const DASH_DASH_DOT: i32 = 3;
+
 @group(2) @binding(0)
 var<uniform> material: LineMaterial;
 
+// Matches the vertex buffer layout built in `LineMaterial::specialize`
+struct LineVertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+#ifdef VERTEX_NORMALS
+    @location(1) normal: vec3<f32>,
+#endif
+#ifdef LINE_EDGE_CLASS
+    @location(2) edge_class: f32,
+#endif
+#ifdef VERTEX_COLORS
+    @location(5) color: vec4<f32>,
+#endif
+#ifdef SKINNED
+    @location(6) joint_indices: vec4<u32>,
+    @location(7) joint_weights: vec4<f32>,
+#endif
+#ifdef LINE_FACE_NORMALS
+    @location(8) face_normal_a: vec3<f32>,
+    @location(9) face_normal_b: vec3<f32>,
+#endif
+#ifdef THICK_LINES
+    @location(10) other_end: vec3<f32>,
+    @location(11) line_side: vec2<f32>,
+#ifdef SKINNED
+    @location(12) other_joint_indices: vec4<u32>,
+    @location(13) other_joint_weights: vec4<f32>,
+#endif
+#endif
+#ifdef LINE_DISTANCE
+    @location(14) line_distance: f32,
+#endif
+#ifdef LINE_REVEAL
+    @location(15) line_reveal: vec3<f32>,
+#endif
+#ifdef MORPH_TARGETS
+    @builtin(vertex_index) index: u32,
+#endif
+};
+
+struct LineVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) color: vec4<f32>,
+    // 1.0 on silhouette edges, 0.0 elsewhere
+    @location(3) silhouette: f32,
+#ifdef THICK_LINES
+    // Pixels along the line from its start, pixels across it, its length and half its width
+    @location(4) @interpolate(linear) line_coord: vec4<f32>,
+#endif
+#ifdef LINE_DISTANCE
+    @location(5) line_distance: f32,
+#endif
+#ifdef LINE_REVEAL
+    @location(6) @interpolate(flat) line_reveal: vec3<f32>,
+#endif
+#ifdef LINE_EDGE_CLASS
+    @location(7) @interpolate(flat) edge_class: u32,
+#endif
+};
+
+struct ExpandedLine {
+    position: vec4<f32>,
+    line_coord: vec4<f32>,
+};
+
+// Moves a corner of a thick line's quad sideways in screen space, and past the line's end
+// for square and round caps
+fn expand_line(clip_this: vec4<f32>, clip_other_unclipped: vec4<f32>, line_side: vec2<f32>) -> ExpandedLine {
+    let resolution = view.viewport.zw;
+
+    // Pull an other end behind the camera back onto the near side, otherwise its projection
+    // flips and the quad points the wrong way
+    var clip_other = clip_other_unclipped;
+    if (clip_other.w < 1e-4 && clip_this.w > 1e-4) {
+        let t = (clip_this.w - 1e-4) / (clip_this.w - clip_other.w);
+        clip_other = mix(clip_this, clip_other, t);
+    }
+
+    let screen_this = clip_this.xy / clip_this.w * 0.5 * resolution;
+    let screen_other = clip_other.xy / clip_other.w * 0.5 * resolution;
+    let delta = screen_other - screen_this;
+    let length_px = length(delta);
+    var direction = vec2<f32>(1.0, 0.0);
+    if (length_px > 1e-5) {
+        direction = delta / length_px;
+    }
+    let perpendicular = vec2<f32>(-direction.y, direction.x);
+
+    var half_width = material.width * 0.5;
+    if (material.width_in_world_units > 0) {
+        // Pixels per world unit at this end's depth
+        half_width *= 0.5 * resolution.y * view.clip_from_view[1][1] / clip_this.w;
+    }
+    // Thinner than a pixel would break up into dots
+    half_width = max(half_width, 0.5);
+
+    var extension = 0.0;
+    if (material.line_cap != LINE_CAP_BUTT) {
+        extension = half_width;
+    }
+
+    let offset = perpendicular * line_side.x * half_width - direction * extension;
+
+    var out: ExpandedLine;
+    out.position = vec4<f32>(clip_this.xy + offset / (0.5 * resolution) * clip_this.w, clip_this.zw);
+    let along = select(-extension, length_px + extension, line_side.y > 0.5);
+    out.line_coord = vec4<f32>(along, line_side.x * half_width, length_px, half_width);
+    return out;
+}
+
+// Fades colors towards the fog color between the near and far view distances
+fn depth_fog(color: vec4<f32>, world_position: vec3<f32>) -> vec4<f32> {
+    if (material.fog_far <= material.fog_near) {
+        return color;
+    }
+    let view_depth = -(view.view_from_world * vec4<f32>(world_position, 1.0)).z;
+    let fog = clamp((view_depth - material.fog_near) / (material.fog_far - material.fog_near), 0.0, 1.0);
+    return vec4<f32>(mix(color.rgb, material.fog_color.rgb, fog * material.fog_color.a), color.a);
+}
+
+// Whether a point `distance` along a line falls on the dash pattern
+fn on_dash(distance: f32) -> bool {
+    let dot_length = material.dash_length * 0.2;
+    var period = material.dash_length + material.gap_length;
+    if (material.dash_pattern == DASH_DOTTED) {
+        period = dot_length + material.gap_length;
+    } else if (material.dash_pattern == DASH_DASH_DOT) {
+        period = material.dash_length + dot_length + 2.0 * material.gap_length;
+    }
+    if (material.dash_pattern == DASH_SOLID || period <= 0.0) {
+        return true;
+    }
+
+    let shifted = distance - material.dash_offset - material.dash_speed * globals.time;
+    let t = shifted - period * floor(shifted / period);
+
+    if (material.dash_pattern == DASH_DOTTED) {
+        return t < dot_length;
+    }
+    if (material.dash_pattern == DASH_DASH_DOT) {
+        let dot_start = material.dash_length + material.gap_length;
+        return t < material.dash_length || (t >= dot_start && t < dot_start + dot_length);
+    }
+    return t < material.dash_length;
+}
+
+// Whether a point `distance` along a line has been drawn on yet. Each line grows over
+// `reveal_spread` of the progress, starting at its order
+fn revealed(line_reveal: vec3<f32>, distance: f32) -> bool {
+    let spread = max(material.reveal_spread, 1e-4);
+    let grown = clamp((material.reveal_progress * (1.0 + spread) - line_reveal.x) / spread, 0.0, 1.0);
+    var along = 0.0;
+    if (line_reveal.y > 0.0) {
+        along = clamp(distance / line_reveal.y, 0.0, 1.0);
+    }
+    if (line_reveal.z > 0.5) {
+        along = 1.0 - along;
+    }
+    return grown > 0.0 && along <= grown;
+}
+
+#ifdef MORPH_TARGETS
+fn morph_vertex(vertex_in: LineVertex) -> LineVertex {
+    var vertex = vertex_in;
+    let weight_count = bevy_pbr::morph::layer_count();
+    for (var i: u32 = 0u; i < weight_count; i ++) {
+        let weight = bevy_pbr::morph::weight_at(i);
+        if (weight == 0.0) {
+            continue;
+        }
+        vertex.position += weight * morph(vertex.index, bevy_pbr::morph::position_offset, i);
+#ifdef VERTEX_NORMALS
+        vertex.normal += weight * morph(vertex.index, bevy_pbr::morph::normal_offset, i);
+#endif
+#ifdef THICK_LINES
+        // The corners of a quad are laid out start, start, end, end, so the matching
+        // corner at the other end is two vertices away
+        vertex.other_end += weight * morph(vertex.index ^ 2u, bevy_pbr::morph::position_offset, i);
+#endif
+    }
+    return vertex;
+}
+#endif
+
 @vertex
-fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
-    var out: VertexOutput;
+fn vertex(vertex_no_morph: LineVertex) -> LineVertexOutput {
+    var out: LineVertexOutput;
 
 #ifdef MORPH_TARGETS
     var vertex = morph_vertex(vertex_no_morph);
@@ -78,7 +298,9 @@ fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
     var world_from_local = mesh_functions::get_world_from_local(vertex_no_morph.instance_index);
 #endif
 
+    var normal = vec3<f32>(0.0);
 #ifdef VERTEX_NORMALS
+    normal = vertex.normal;
 #ifdef SKINNED
     out.world_normal = skinning::skin_normals(world_from_local, vertex.normal);
 #else
@@ -91,53 +313,110 @@ fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
 #endif
 #endif
 
-#ifdef VERTEX_POSITIONS
-    vertex.position = vertex.position + (vertex.normal * (material.displacement * 0.15 + 0.001));
-    out.world_position = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0));
+    let position = vertex.position + (normal * (material.displacement * 0.15 + 0.001));
+    out.world_position = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(position, 1.0));
     out.position = position_world_to_clip(out.world_position.xyz);
-#endif
 
-#ifdef VERTEX_UVS_A
-    out.uv = vertex.uv;
+#ifdef LINE_DISTANCE
+    out.line_distance = vertex.line_distance;
 #endif
-#ifdef VERTEX_UVS_B
-    out.uv_b = vertex.uv_b;
+#ifdef LINE_REVEAL
+    out.line_reveal = vertex.line_reveal;
+#endif
+#ifdef LINE_EDGE_CLASS
+    out.edge_class = min(u32(vertex.edge_class + 0.5), EDGE_CLASS_COUNT - 1u);
 #endif
 
-#ifdef VERTEX_TANGENTS
-    out.world_tangent = mesh_functions::mesh_tangent_local_to_world(
-        world_from_local,
-        vertex.tangent,
-        // Use vertex_no_morph.instance_index instead of vertex.instance_index to work around a wgpu dx12 bug.
-        // See https://github.com/gfx-rs/naga/issues/2416
-        vertex_no_morph.instance_index
-    );
+#ifdef THICK_LINES
+#ifdef SKINNED
+    let other_world_from_local = skinning::skin_model(vertex.other_joint_indices, vertex.other_joint_weights);
+#else
+    let other_world_from_local = world_from_local;
+#endif
+    // The other end is displaced along this end's normal, close enough for the small
+    // offsets used to lift lines off the fill
+    let other_position = vertex.other_end + (normal * (material.displacement * 0.15 + 0.001));
+    let other_world_position = mesh_functions::mesh_position_local_to_world(other_world_from_local, vec4<f32>(other_position, 1.0));
+    let expanded = expand_line(out.position, position_world_to_clip(other_world_position.xyz), vertex.line_side);
+    out.position = expanded.position;
+    out.line_coord = expanded.line_coord;
 #endif
 
 #ifdef VERTEX_COLORS
-    out.color = vertex.color;
+    if (material.use_vertex_color > 0) {
+        out.color = vertex.color;
+    } else {
+        out.color = material.color;
+    }
+#else
+    out.color = material.color;
 #endif
 
-#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
-    // Use vertex_no_morph.instance_index instead of vertex.instance_index to work around a wgpu dx12 bug.
-    // See https://github.com/gfx-rs/naga/issues/2416
-    out.instance_index = vertex_no_morph.instance_index;
+    out.silhouette = 1.0;
+#ifdef LINE_FACE_NORMALS
+    if (material.silhouette_mode != SILHOUETTE_OFF) {
+#ifdef SKINNED
+        let normal_a = skinning::skin_normals(world_from_local, vertex.face_normal_a);
+        let normal_b = skinning::skin_normals(world_from_local, vertex.face_normal_b);
+#else
+        let normal_a = mesh_functions::mesh_normal_local_to_world(vertex.face_normal_a, vertex_no_morph.instance_index);
+        let normal_b = mesh_functions::mesh_normal_local_to_world(vertex.face_normal_b, vertex_no_morph.instance_index);
 #endif
-
-#ifdef VISIBILITY_RANGE_DITHER
-    out.visibility_range_dither = mesh_functions::get_visibility_range_dither_level(
-        vertex_no_morph.instance_index, world_from_local[3]);
+        // An edge is on the silhouette when one adjacent face points towards the camera
+        // and the other away from it
+        let to_camera = view.world_position.xyz - out.world_position.xyz;
+        let facing_a = dot(normal_a, to_camera);
+        let facing_b = dot(normal_b, to_camera);
+        out.silhouette = select(0.0, 1.0, facing_a * facing_b <= 0.0);
+    }
 #endif
 
     return out;
 }
 
 @fragment
-fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-    // #ifdef VERTEX_COLORS
-    // return in.color;
-    // #else
-    return in.color;
-    // #endif
-    
-}
\ No newline at end of file
+fn fragment(in: LineVertexOutput) -> @location(0) vec4<f32> {
+    var color = in.color;
+
+#ifdef LINE_EDGE_CLASS
+    let class_color = material.edge_class_colors[in.edge_class];
+    if (class_color.a <= 0.0) {
+        discard;
+    }
+    color *= class_color;
+#endif
+#ifdef LINE_DISTANCE
+    if (!on_dash(in.line_distance)) {
+        discard;
+    }
+#endif
+#ifdef LINE_REVEAL
+    if (!revealed(in.line_reveal, in.line_distance)) {
+        discard;
+    }
+#endif
+
+#ifdef THICK_LINES
+    // Round caps are cut out of the square extension past each end
+    if (material.line_cap == LINE_CAP_ROUND) {
+        let past_end = max(-in.line_coord.x, in.line_coord.x - in.line_coord.z);
+        if (past_end > 0.0 && length(vec2<f32>(past_end, in.line_coord.y)) > in.line_coord.w) {
+            discard;
+        }
+    }
+#endif
+
+    // The silhouette flag is interpolated, so an edge that only just turned is cut off
+    // part way instead of popping
+    if (material.silhouette_mode == SILHOUETTE_ONLY && in.silhouette < 0.5) {
+        discard;
+    }
+    if (material.silhouette_mode == SILHOUETTE_FADE) {
+        color.a *= mix(material.silhouette_fade, 1.0, in.silhouette);
+    }
+
+    color = vec4<f32>(color.rgb * material.intensity, color.a);
+    color = depth_fog(color, in.world_position.xyz);
+
+    return color;
+}
@@ -13,11 +13,25 @@ struct OutlineMaterial {
     outline_width: f32,
     z_translate: f32,
     use_vertex_color: i32,
+    fog_near: f32,
+    fog_far: f32,
+    fog_color: vec4<f32>,
+    intensity: f32,
 };
 
 @group(2) @binding(0)
 var<uniform> material: OutlineMaterial;
 
+// Fades colors towards the fog color between the near and far view distances
+fn depth_fog(color: vec4<f32>, world_position: vec3<f32>) -> vec4<f32> {
+    if (material.fog_far <= material.fog_near) {
+        return color;
+    }
+    let view_depth = -(view.view_from_world * vec4<f32>(world_position, 1.0)).z;
+    let fog = clamp((view_depth - material.fog_near) / (material.fog_far - material.fog_near), 0.0, 1.0);
+    return vec4<f32>(mix(color.rgb, material.fog_color.rgb, fog * material.fog_color.a), color.a);
+}
+
 #ifdef MORPH_TARGETS
 fn morph_vertex(vertex_in: Vertex) -> Vertex {
     var vertex = vertex_in;
@@ -108,6 +122,7 @@ fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    let color = vec4<f32>(in.color.rgb * material.intensity, in.color.a);
+    return depth_fog(color, in.world_position.xyz);
     // return vec4(1.0,1.0,1.0,1.0);
 }
@@ -1,4 +1,4 @@
 #!/bin/bash
 set -e 
-cargo build --release --target wasm32-unknown-unknown
-wasm-bindgen --out-dir ./out/ --target web ./target/wasm32-unknown-unknown/release/bevy_wireframe.wasm
+cargo build --release --example demo --target wasm32-unknown-unknown
+wasm-bindgen --out-dir ./out/ --out-name bevy_wireframe --target web ./target/wasm32-unknown-unknown/release/examples/demo.wasm
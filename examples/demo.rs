@@ -0,0 +1,229 @@
+use bevy::{
+    animation::animate_targets,
+    core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping},
+    prelude::*,
+};
+
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
+use bevy_wireframe::{mesh_ops::EDGE_CLASS_COUNT, prelude::*};
+use std::time::Duration;
+
+const ASTROPATH: &str = "astro_custom/scene.gltf";
+
+#[derive(Resource)]
+struct Animations {
+    animations: Vec<AnimationNodeIndex>,
+    #[allow(dead_code)]
+    graph: Handle<AnimationGraph>,
+}
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_plugins(DefaultPlugins.set(WireframePlugin::gltf_plugin()))
+        .add_plugins(
+            WireframePlugin::new()
+                .with_line_intensity(20.0)
+                .with_outline_intensity(20.0),
+        )
+        .add_plugins(EguiPlugin)
+        .add_plugins(PanOrbitCameraPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, play_animation_once_loaded.before(animate_targets))
+        .add_systems(Update, ui_system)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                hdr: true,
+                ..default()
+            },
+            tonemapping: Tonemapping::TonyMcMapface,
+            transform: Transform::from_translation(Vec3::new(0.0, 1.5, 5.0)),
+            ..default()
+        },
+        PanOrbitCamera::default(),
+        BloomSettings::NATURAL,
+    ));
+
+    // Build the animation graph
+    let mut graph = AnimationGraph::new();
+    let animations = graph
+        .add_clips(
+            [GltfAssetLabel::Animation(0).from_asset(ASTROPATH)]
+                .into_iter()
+                .map(|path| assets.load(path)),
+            1.0,
+            graph.root,
+        )
+        .collect();
+
+    // Insert a resource with the current scene information
+    let graph = graphs.add(graph);
+    commands.insert_resource(Animations {
+        animations,
+        graph: graph.clone(),
+    });
+
+    // Boundaries bright, creases medium, the other edges faint
+    let mut edge_class_colors = [Vec4::splat(0.2); EDGE_CLASS_COUNT];
+    edge_class_colors[EdgeClass::Boundary as usize] = Vec4::ONE;
+    edge_class_colors[EdgeClass::Crease as usize] = Vec4::splat(0.6);
+
+    commands.spawn((
+        SceneBundle {
+            scene: assets.load(GltfAssetLabel::Scene(0).from_asset(ASTROPATH)),
+            transform: Transform::from_xyz(0.0, -1.2, 0.0)
+                .with_rotation(Quat::from_rotation_y(0.0))
+                .with_scale(Vec3::splat(1.)),
+            ..default()
+        },
+        WireframeSettings {
+            line_edge_class_colors: edge_class_colors,
+            sidecar_edge_list: true,
+            ..default()
+        },
+        RenderStyle::default(),
+    ));
+
+    // Meshes spawned without a scene convert the same way
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Torus::new(0.3, 0.5)),
+            material: materials.add(Color::WHITE),
+            transform: Transform::from_xyz(1.5, 0.0, 0.0),
+            ..default()
+        },
+        WireframeSettings {
+            edge_mode: EdgeMode::Quads {
+                coplanar_tolerance: 1f32.to_radians(),
+            },
+            ..default()
+        },
+        RenderStyle::default(),
+    ));
+}
+
+fn play_animation_once_loaded(
+    mut commands: Commands,
+    animations: Res<Animations>,
+    mut players: Query<(Entity, &mut AnimationPlayer), Added<AnimationPlayer>>,
+) {
+    for (entity, mut player) in &mut players {
+        let mut transitions = AnimationTransitions::new();
+
+        // Make sure to start the animation via the `AnimationTransitions`
+        // component. The `AnimationTransitions` component wants to manage all
+        // the animations and will get confused if the animations are started
+        // directly via the `AnimationPlayer`.
+        transitions
+            .play(&mut player, animations.animations[0], Duration::ZERO)
+            .repeat();
+
+        commands
+            .entity(entity)
+            .insert(animations.graph.clone())
+            .insert(transitions);
+    }
+}
+
+fn ui_system(
+    mut contexts: EguiContexts,
+    mut shader_settings: ResMut<ShaderSettings>,
+    progress: Res<WireframeProgress>,
+    mut render_styles: Query<&mut RenderStyle>,
+) {
+    let mut changed = false;
+    // The sliders borrow the settings every frame, only a moved slider counts as a change
+    let settings = shader_settings.bypass_change_detection();
+
+    egui::Window::new("Shader Controls").show(contexts.ctx_mut(), |ui| {
+        if !progress.is_done() {
+            ui.add(
+                egui::ProgressBar::new(progress.fraction())
+                    .text(format!("Converting meshes ({} left)", progress.pending)),
+            );
+        }
+        for mut style in &mut render_styles {
+            ui.horizontal(|ui| {
+                for (value, label) in [
+                    (RenderStyle::Original, "Original"),
+                    (RenderStyle::Wireframe, "Wireframe"),
+                    (RenderStyle::FillPlusWire, "Fill + Wire"),
+                    (RenderStyle::OutlineOnly, "Outline"),
+                    (RenderStyle::HiddenLine, "Hidden Line"),
+                ] {
+                    // Only write on a click, so the style isn't marked changed every frame
+                    if ui.selectable_label(*style == value, label).clicked() {
+                        *style = value;
+                    }
+                }
+            });
+        }
+        changed |= ui
+            .add(egui::Slider::new(&mut settings.outline_width, 0.0..=1.0).text("Outline Width"))
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut settings.wireframe_displacement, 0.0..=2.0)
+                    .text("Wireframe Displacement"),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut settings.fill_displacement, 0.0..=1.0)
+                    .text("Fill Displacement"),
+            )
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut settings.fill_shininess, 1.0..=256.0).text("Shininess"))
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut settings.fill_specular_strength, 0.0..=1.0)
+                    .text("Specular Strength"),
+            )
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut settings.fog_near, 0.0..=50.0).text("Fog Near"))
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut settings.fog_far, 0.0..=100.0).text("Fog Far"))
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut settings.line_intensity, 0.0..=50.0).text("Line Intensity"))
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut settings.outline_intensity, 0.0..=50.0)
+                    .text("Outline Intensity"),
+            )
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut settings.fill_intensity, 0.0..=50.0).text("Fill Intensity"))
+            .changed();
+        ui.horizontal(|ui| {
+            let mut fog_color = settings.fog_color.to_array();
+            changed |= ui
+                .color_edit_button_rgba_unmultiplied(&mut fog_color)
+                .changed();
+            settings.fog_color = Vec4::from_array(fog_color);
+            ui.label("Fog Color");
+        });
+    });
+
+    // The plugin passes the change on to every material whose scene doesn't override it
+    if changed {
+        shader_settings.set_changed();
+    }
+}
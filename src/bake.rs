@@ -0,0 +1,951 @@
+use std::{
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{
+            morph::{MorphAttributes, MorphTargetImage},
+            Indices, MeshVertexAttribute, VertexAttributeValues,
+        },
+        render_asset::RenderAssetUsages,
+        render_resource::PrimitiveTopology,
+    },
+    utils::HashMap,
+};
+use gltf::{
+    accessor::{DataType, Dimensions},
+    json::{
+        self,
+        accessor::{ComponentType, GenericComponentType, Type},
+        animation::Property,
+        validation::{Checked::Valid, USize64},
+    },
+    mesh::Mode,
+    Semantic,
+};
+use serde_json::{value::RawValue, Map, Value};
+
+use crate::{
+    gltf_extras::{parse_or_warn, WireframeExtras},
+    load_json::{json_parse, JsonLineList, JSON_LINE_LIST_EXTENSION},
+    mesh_ops::{boundary_edges, read_morph_targets},
+    vertex_colors::{VertexColorSource, VertexColorStrategy},
+    wireframe_plugin::convert_mesh,
+    WireframeSettings, ATTRIBUTE_INDEX, ATTRIBUTE_MATERIAL_ID, GLTF_INDEX_ATTRIBUTE,
+    GLTF_LINE_ATTRIBUTES, GLTF_MATERIAL_ID_ATTRIBUTE,
+};
+
+/// How [`bake_gltf`] converts a scene.
+#[derive(Clone, Default)]
+pub struct BakeOptions {
+    /// Only the settings that shape the generated meshes are used: `edge_mode`,
+    /// `vertex_colors`, `weld_tolerance`, `reveal_order`, `sidecar_edge_list` and
+    /// `layers.line`. Styling is still up to the [`WireframeSettings`] at runtime.
+    pub settings: WireframeSettings,
+    /// Used instead of the sidecar edge list next to the input.
+    pub edge_list: Option<JsonLineList>,
+}
+
+/// A `.glb` written by [`bake_gltf`].
+pub struct BakedGltf {
+    pub glb: Vec<u8>,
+    /// Number of triangle primitives that were converted.
+    pub primitives: usize,
+    /// Number of lines across all line primitives.
+    pub lines: usize,
+}
+
+/// Runs the conversion of [`crate::WireframePlugin`] on every triangle primitive of a glTF,
+/// without Bevy's asset server or a GPU, and writes the result as a self-contained `.glb`.
+///
+/// Each converted primitive is replaced by its flat shaded fill mesh and a `LINES`
+/// primitive carrying the [`GLTF_LINE_ATTRIBUTES`], both marked with the `wireframe_baked`
+/// extra so the plugin only attaches materials to them. Nodes, skins, animations and
+/// materials are kept, morph targets are remapped onto both primitives and textures are
+/// embedded.
+///
+/// Lines are baked as hardware lines, so a `line_width` set at runtime has no effect on
+/// them. [`VertexColorStrategy::Texture`] falls back to the base color, textures aren't
+/// decoded when baking.
+pub fn bake_gltf(path: &Path, options: &BakeOptions) -> Result<BakedGltf> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let gltf = gltf::Gltf::from_slice(&bytes)?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let buffers = load_buffers(&gltf, base_dir)?;
+
+    let sidecar = match &options.edge_list {
+        Some(edge_list) => Some(edge_list.clone()),
+        None if options.settings.sidecar_edge_list => load_sidecar(path),
+        None => None,
+    };
+
+    if options.settings.vertex_colors == VertexColorStrategy::Texture {
+        warn!("Textures aren't sampled when baking, using the base colors instead");
+    }
+
+    // Convert first, the JSON is rewritten afterwards
+    let mut converted: HashMap<(usize, usize), BakedPrimitive> = HashMap::new();
+    let mut lines = 0;
+    for mesh in gltf.document.meshes() {
+        // A mesh shared by several nodes is converted once, with the first node's extras
+        let node = gltf.document.nodes().find(|node| {
+            node.mesh()
+                .is_some_and(|node_mesh| node_mesh.index() == mesh.index())
+        });
+
+        for primitive in mesh.primitives() {
+            let mut extras = WireframeExtras::default();
+            for json_extras in [
+                node.as_ref().map(|node| node.extras()),
+                Some(mesh.extras()),
+                Some(primitive.material().extras()),
+                Some(primitive.extras()),
+            ]
+            .into_iter()
+            .flatten()
+            .flatten()
+            {
+                extras.merge(parse_or_warn(json_extras.get()));
+            }
+            if extras.skip() || extras.baked() {
+                continue;
+            }
+            let Some((source_mesh, morph_targets)) = read_primitive(&primitive, &buffers) else {
+                continue;
+            };
+
+            // The same names the plugin sees on the spawned entities
+            let primitive_name = match mesh.primitives().len() {
+                1 => mesh.name().unwrap_or("Mesh").to_string(),
+                _ => format!("{}.{}", mesh.name().unwrap_or("Mesh"), primitive.index()),
+            };
+            let node_name = node.as_ref().map(|node| match node.name() {
+                Some(name) => name.to_string(),
+                None => format!("GltfNode{}", node.index()),
+            });
+            let mesh_names: Vec<String> = [Some(primitive_name), node_name]
+                .into_iter()
+                .flatten()
+                .collect();
+
+            let names: Vec<&str> = mesh_names.iter().map(String::as_str).collect();
+            let sidecar_edge_list = sidecar
+                .as_ref()
+                .and_then(|sidecar| sidecar.line_list_for(&names, extras.gltf_primitive_index));
+            let edge_list = extras.edge_list().or(sidecar_edge_list);
+
+            let mut settings = options.settings.clone();
+            extras.apply(&mut settings);
+
+            let mut hasher = DefaultHasher::new();
+            mesh_names.hash(&mut hasher);
+            let color_source = VertexColorSource {
+                mesh_key: hasher.finish(),
+                base_color: Some(Vec4::from(
+                    primitive
+                        .material()
+                        .pbr_metallic_roughness()
+                        .base_color_factor(),
+                )),
+                base_color_texture: None,
+                weld_tolerance: settings.weld_tolerance,
+            };
+
+            // Primitives of the mesh with another material meet this one at material
+            // boundaries
+            let material_seams: Vec<[Vec3; 2]> = match settings.layers.line {
+                true => mesh
+                    .primitives()
+                    .filter(|other| other.material().index() != primitive.material().index())
+                    .filter_map(|other| read_primitive(&other, &buffers))
+                    .flat_map(|(other, _)| boundary_edges(&other, settings.weld_tolerance))
+                    .collect(),
+                false => vec![],
+            };
+
+            let result = convert_mesh(
+                source_mesh,
+                &settings,
+                &color_source,
+                edge_list,
+                false,
+                morph_targets.as_ref(),
+                &material_seams,
+            );
+            let line_mesh = result
+                .line_mesh
+                .filter(|line_mesh| line_mesh.count_vertices() > 0);
+            lines += line_mesh
+                .as_ref()
+                .map_or(0, |line_mesh| line_mesh.count_vertices() / 2);
+            converted.insert(
+                (mesh.index(), primitive.index()),
+                BakedPrimitive {
+                    flat_mesh: result.flat_mesh,
+                    line_mesh,
+                    target_count: primitive.morph_targets().len(),
+                    flat_morph_targets: result.flat_morph_targets,
+                    line_morph_targets: result.line_morph_targets,
+                },
+            );
+        }
+    }
+
+    let primitives = converted.len();
+    let mut root = gltf.document.into_json();
+    let mut writer = GlbWriter::new(&buffers, &mut root);
+
+    for skin in &mut root.skins {
+        if let Some(matrices) = skin.inverse_bind_matrices {
+            skin.inverse_bind_matrices = Some(writer.copy_accessor(matrices));
+        }
+    }
+    let mut meshes_without_targets = vec![];
+    for (mesh_index, mesh) in root.meshes.iter_mut().enumerate() {
+        let mut primitives = vec![];
+        for (primitive_index, primitive) in mesh.primitives.drain(..).enumerate() {
+            let Some(baked) = converted.remove(&(mesh_index, primitive_index)) else {
+                primitives.push(writer.copy_primitive(primitive));
+                continue;
+            };
+
+            let extras = baked_extras(&primitive.extras);
+            primitives.push(json::mesh::Primitive {
+                material: primitive.material,
+                extras: extras.clone(),
+                targets: writer.write_morph_targets(
+                    baked.flat_morph_targets.as_ref(),
+                    baked.flat_mesh.count_vertices(),
+                    baked.target_count,
+                ),
+                ..writer.write_mesh(&baked.flat_mesh, json::mesh::Mode::Triangles)
+            });
+            if let Some(line_mesh) = baked.line_mesh {
+                primitives.push(json::mesh::Primitive {
+                    extras,
+                    targets: writer.write_morph_targets(
+                        baked.line_morph_targets.as_ref(),
+                        line_mesh.count_vertices(),
+                        baked.target_count,
+                    ),
+                    ..writer.write_mesh(&line_mesh, json::mesh::Mode::Lines)
+                });
+            }
+        }
+        mesh.primitives = primitives;
+
+        // Primitives that failed to read lose their targets
+        if mesh
+            .primitives
+            .iter()
+            .all(|primitive| primitive.targets.is_none())
+        {
+            mesh.weights = None;
+            meshes_without_targets.push(mesh_index);
+        }
+    }
+    let mut nodes_without_targets = vec![];
+    for (node_index, node) in root.nodes.iter_mut().enumerate() {
+        if node
+            .mesh
+            .is_some_and(|mesh| meshes_without_targets.contains(&mesh.value()))
+        {
+            node.weights = None;
+            nodes_without_targets.push(node_index);
+        }
+    }
+
+    // Weight channels of meshes without targets are invalid, and so are the samplers and
+    // animations they leave empty
+    for animation in &mut root.animations {
+        animation.channels.retain(|channel| {
+            channel.target.path != Valid(Property::MorphTargetWeights)
+                || !nodes_without_targets.contains(&channel.target.node.value())
+        });
+        let mut used_samplers: Vec<usize> = animation
+            .channels
+            .iter()
+            .map(|channel| channel.sampler.value())
+            .collect();
+        used_samplers.sort_unstable();
+        used_samplers.dedup();
+        for channel in &mut animation.channels {
+            let sampler = used_samplers.binary_search(&channel.sampler.value());
+            channel.sampler = json::Index::new(sampler.unwrap_or_default() as u32);
+        }
+        animation.samplers = used_samplers
+            .iter()
+            .map(|&sampler| animation.samplers[sampler].clone())
+            .collect();
+
+        for sampler in &mut animation.samplers {
+            sampler.input = writer.copy_accessor(sampler.input);
+            sampler.output = writer.copy_accessor(sampler.output);
+        }
+    }
+    root.animations
+        .retain(|animation| !animation.channels.is_empty());
+
+    for image in &mut root.images {
+        if let Some(view) = image.buffer_view {
+            image.buffer_view = Some(writer.copy_view(view));
+        } else if let Some(uri) = image.uri.take() {
+            let (data, mime_type) = read_uri(&uri, base_dir)?;
+            image.buffer_view = Some(writer.push_view(&data, None));
+            image.mime_type = mime_type
+                .or_else(|| mime_type_from_path(&uri))
+                .map(json::image::MimeType);
+        }
+    }
+
+    let glb = writer.finish(root)?;
+    Ok(BakedGltf {
+        glb,
+        primitives,
+        lines,
+    })
+}
+
+/// The contents of every buffer of the glTF, by index.
+fn load_buffers(gltf: &gltf::Gltf, base_dir: &Path) -> Result<Vec<Vec<u8>>> {
+    gltf.document
+        .buffers()
+        .map(|buffer| match buffer.source() {
+            gltf::buffer::Source::Bin => gltf
+                .blob
+                .clone()
+                .ok_or_else(|| anyhow!("glTF refers to a missing binary chunk")),
+            gltf::buffer::Source::Uri(uri) => Ok(read_uri(uri, base_dir)?.0),
+        })
+        .collect()
+}
+
+fn load_sidecar(path: &Path) -> Option<JsonLineList> {
+    let sidecar = path.with_extension(JSON_LINE_LIST_EXTENSION);
+    let bytes = fs::read(&sidecar).ok()?;
+    match json_parse(&bytes) {
+        Ok(edge_list) => Some(edge_list),
+        Err(e) => {
+            warn!("Ignoring edge list {}: {}", sidecar.display(), e);
+            None
+        }
+    }
+}
+
+/// Reads a data URI or a file relative to the glTF, along with the MIME type of a data URI.
+fn read_uri(uri: &str, base_dir: &Path) -> Result<(Vec<u8>, Option<String>)> {
+    if let Some(data_uri) = uri.strip_prefix("data:") {
+        let (header, data) = data_uri
+            .split_once(',')
+            .ok_or_else(|| anyhow!("Malformed data URI"))?;
+        let Some(mime_type) = header.strip_suffix(";base64") else {
+            return Err(anyhow!("Only base64 data URIs are supported"));
+        };
+        let mime_type = (!mime_type.is_empty()).then(|| mime_type.to_string());
+        return Ok((STANDARD.decode(data)?, mime_type));
+    }
+
+    let path: PathBuf = base_dir.join(percent_decode(uri));
+    let data = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok((data, None))
+}
+
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn mime_type_from_path(path: &str) -> Option<String> {
+    let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    let mime_type = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "ktx2" => "image/ktx2",
+        "webp" => "image/webp",
+        _ => return None,
+    };
+    Some(mime_type.to_string())
+}
+
+/// A converted primitive, waiting for the JSON to be rewritten.
+struct BakedPrimitive {
+    flat_mesh: Mesh,
+    line_mesh: Option<Mesh>,
+    /// Number of morph targets of the source primitive, which both primitives must match.
+    target_count: usize,
+    flat_morph_targets: Option<Image>,
+    line_morph_targets: Option<Image>,
+}
+
+/// The attributes of a triangle primitive that the conversion uses and its morph target
+/// image, `None` for other primitive modes.
+fn read_primitive(
+    primitive: &gltf::Primitive,
+    buffers: &[Vec<u8>],
+) -> Option<(Mesh, Option<Image>)> {
+    if primitive.mode() != Mode::Triangles {
+        return None;
+    }
+    let get_buffer = |buffer: gltf::Buffer| buffers.get(buffer.index()).map(Vec::as_slice);
+    let reader = primitive.reader(get_buffer);
+
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD,
+    );
+    let positions: Vec<[f32; 3]> = reader.read_positions()?.collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    if let Some(normals) = reader.read_normals() {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals.collect::<Vec<_>>());
+    }
+    if let Some(uvs) = reader.read_tex_coords(0) {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs.into_f32().collect::<Vec<_>>());
+    }
+    if let Some(colors) = reader.read_colors(0) {
+        let colors: Vec<[f32; 4]> = colors.into_rgba_f32().collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+    if let (Some(joints), Some(weights)) = (reader.read_joints(0), reader.read_weights(0)) {
+        let joints: Vec<[u16; 4]> = joints.into_u16().collect();
+        let weights: Vec<[f32; 4]> = weights.into_f32().collect();
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_JOINT_INDEX,
+            VertexAttributeValues::Uint16x4(joints),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, weights);
+    }
+    for (name, attribute) in [
+        (GLTF_INDEX_ATTRIBUTE, ATTRIBUTE_INDEX),
+        (GLTF_MATERIAL_ID_ATTRIBUTE, ATTRIBUTE_MATERIAL_ID),
+    ] {
+        let Some(accessor) = primitive.get(&Semantic::Extras(name.to_string())) else {
+            continue;
+        };
+        if accessor.data_type() == DataType::F32 && accessor.dimensions() == Dimensions::Scalar {
+            let values = gltf::accessor::Iter::<f32>::new(accessor, get_buffer);
+            if let Some(values) = values {
+                mesh.insert_attribute(attribute, values.collect::<Vec<f32>>());
+            }
+        } else {
+            warn!("Ignoring a {} attribute that isn't a float", name);
+        }
+    }
+    if let Some(indices) = reader.read_indices() {
+        mesh.insert_indices(Indices::U32(indices.into_u32().collect()));
+    }
+
+    let vertex_count = mesh.count_vertices();
+    let mut targets: Vec<Vec<MorphAttributes>> = reader
+        .read_morph_targets()
+        .map(|(positions, normals, tangents)| {
+            let mut positions = positions.into_iter().flatten();
+            let mut normals = normals.into_iter().flatten();
+            let mut tangents = tangents.into_iter().flatten();
+            (0..vertex_count)
+                .map(|_| MorphAttributes {
+                    position: positions.next().map_or(Vec3::ZERO, Vec3::from),
+                    normal: normals.next().map_or(Vec3::ZERO, Vec3::from),
+                    tangent: tangents.next().map_or(Vec3::ZERO, Vec3::from),
+                })
+                .collect()
+        })
+        .collect();
+
+    // Like the glTF loader, meshes without normals are flat shaded
+    if mesh.attribute(Mesh::ATTRIBUTE_NORMAL).is_none() {
+        if let Some(indices) = mesh.indices() {
+            targets = targets
+                .iter()
+                .map(|target| {
+                    indices
+                        .iter()
+                        .map(|index| target.get(index).copied().unwrap_or_default())
+                        .collect()
+                })
+                .collect();
+        }
+        mesh.duplicate_vertices();
+        mesh.compute_flat_normals();
+    }
+
+    let morph_targets = (!targets.is_empty())
+        .then(|| {
+            MorphTargetImage::new(
+                targets.into_iter().map(Vec::into_iter),
+                mesh.count_vertices(),
+                RenderAssetUsages::MAIN_WORLD,
+            )
+            .map_err(|e| warn!("Dropping morph targets: {}", e))
+            .ok()
+        })
+        .flatten();
+    Some((mesh, morph_targets.map(|image| image.0)))
+}
+
+/// The primitive's own extras plus the `wireframe_baked` marker.
+fn baked_extras(extras: &json::Extras) -> json::Extras {
+    let mut object: Map<String, Value> = extras
+        .as_ref()
+        .and_then(|raw| serde_json::from_str(raw.get()).ok())
+        .unwrap_or_default();
+    object.insert("wireframe_baked".to_string(), Value::Bool(true));
+    RawValue::from_string(Value::Object(object).to_string()).ok()
+}
+
+/// Rebuilds the binary chunk of a glTF, keeping only the data that's still referenced and
+/// appending the generated meshes.
+struct GlbWriter<'a> {
+    buffers: &'a [Vec<u8>],
+    source_views: Vec<json::buffer::View>,
+    source_accessors: Vec<json::Accessor>,
+    copied_views: HashMap<usize, json::Index<json::buffer::View>>,
+    copied_accessors: HashMap<usize, json::Index<json::Accessor>>,
+    bin: Vec<u8>,
+    views: Vec<json::buffer::View>,
+    accessors: Vec<json::Accessor>,
+}
+
+impl<'a> GlbWriter<'a> {
+    fn new(buffers: &'a [Vec<u8>], root: &mut json::Root) -> Self {
+        Self {
+            buffers,
+            source_views: std::mem::take(&mut root.buffer_views),
+            source_accessors: std::mem::take(&mut root.accessors),
+            copied_views: HashMap::new(),
+            copied_accessors: HashMap::new(),
+            bin: vec![],
+            views: vec![],
+            accessors: vec![],
+        }
+    }
+
+    fn push_view(
+        &mut self,
+        data: &[u8],
+        target: Option<json::buffer::Target>,
+    ) -> json::Index<json::buffer::View> {
+        self.bin.resize(self.bin.len().next_multiple_of(4), 0);
+        self.views.push(json::buffer::View {
+            buffer: json::Index::new(0),
+            byte_length: USize64::from(data.len()),
+            byte_offset: Some(USize64::from(self.bin.len())),
+            byte_stride: None,
+            name: None,
+            target: target.map(Valid),
+            extensions: None,
+            extras: None,
+        });
+        self.bin.extend_from_slice(data);
+        json::Index::new(self.views.len() as u32 - 1)
+    }
+
+    fn push_accessor(&mut self, accessor: json::Accessor) -> json::Index<json::Accessor> {
+        self.accessors.push(accessor);
+        json::Index::new(self.accessors.len() as u32 - 1)
+    }
+
+    fn copy_view(
+        &mut self,
+        index: json::Index<json::buffer::View>,
+    ) -> json::Index<json::buffer::View> {
+        if let Some(copied) = self.copied_views.get(&index.value()) {
+            return *copied;
+        }
+        let view = self.source_views[index.value()].clone();
+        let start = view.byte_offset.map_or(0, |offset| offset.0 as usize);
+        let end = start + view.byte_length.0 as usize;
+        let data = self
+            .buffers
+            .get(view.buffer.value())
+            .and_then(|buffer| buffer.get(start..end))
+            .unwrap_or_default();
+
+        let copied = self.push_view(data, None);
+        let new_view = &mut self.views[copied.value()];
+        new_view.byte_stride = view.byte_stride;
+        new_view.target = view.target;
+        new_view.name = view.name;
+        self.copied_views.insert(index.value(), copied);
+        copied
+    }
+
+    fn copy_accessor(&mut self, index: json::Index<json::Accessor>) -> json::Index<json::Accessor> {
+        if let Some(copied) = self.copied_accessors.get(&index.value()) {
+            return *copied;
+        }
+        let mut accessor = self.source_accessors[index.value()].clone();
+        accessor.buffer_view = accessor.buffer_view.map(|view| self.copy_view(view));
+        if let Some(sparse) = &mut accessor.sparse {
+            sparse.indices.buffer_view = self.copy_view(sparse.indices.buffer_view);
+            sparse.values.buffer_view = self.copy_view(sparse.values.buffer_view);
+        }
+        let copied = self.push_accessor(accessor);
+        self.copied_accessors.insert(index.value(), copied);
+        copied
+    }
+
+    /// A primitive that isn't converted, with its data carried over.
+    fn copy_primitive(&mut self, mut primitive: json::mesh::Primitive) -> json::mesh::Primitive {
+        for accessor in primitive.attributes.values_mut() {
+            *accessor = self.copy_accessor(*accessor);
+        }
+        primitive.indices = primitive.indices.map(|indices| self.copy_accessor(indices));
+        for target in primitive.targets.iter_mut().flatten() {
+            for accessor in [
+                &mut target.positions,
+                &mut target.normals,
+                &mut target.tangents,
+            ] {
+                *accessor = accessor.map(|accessor| self.copy_accessor(accessor));
+            }
+        }
+        primitive
+    }
+
+    fn write_mesh(&mut self, mesh: &Mesh, mode: json::mesh::Mode) -> json::mesh::Primitive {
+        let mut semantics: Vec<(Semantic, MeshVertexAttribute)> = vec![
+            (Semantic::Positions, Mesh::ATTRIBUTE_POSITION),
+            (Semantic::Normals, Mesh::ATTRIBUTE_NORMAL),
+            (Semantic::TexCoords(0), Mesh::ATTRIBUTE_UV_0),
+            (Semantic::Colors(0), Mesh::ATTRIBUTE_COLOR),
+            (Semantic::Joints(0), Mesh::ATTRIBUTE_JOINT_INDEX),
+            (Semantic::Weights(0), Mesh::ATTRIBUTE_JOINT_WEIGHT),
+        ];
+        semantics.extend(
+            GLTF_LINE_ATTRIBUTES
+                .into_iter()
+                .map(|(name, attribute)| (Semantic::Extras(name.to_string()), attribute)),
+        );
+
+        let mut attributes = std::collections::BTreeMap::new();
+        for (semantic, attribute) in semantics {
+            let Some(values) = mesh.attribute(attribute) else {
+                continue;
+            };
+            let Some((component_type, type_)) = accessor_format(values) else {
+                warn!("Can't write a {:?} {:?} attribute", values, semantic);
+                continue;
+            };
+            let bounds = semantic == Semantic::Positions;
+            let accessor = self.write_attribute(values, component_type, type_, bounds);
+            attributes.insert(Valid(semantic), accessor);
+        }
+
+        let indices = mesh.indices().map(|indices| {
+            let (bytes, component_type): (Vec<u8>, _) = match indices {
+                Indices::U16(indices) => (
+                    indices
+                        .iter()
+                        .flat_map(|index| index.to_le_bytes())
+                        .collect(),
+                    ComponentType::U16,
+                ),
+                Indices::U32(indices) => (
+                    indices
+                        .iter()
+                        .flat_map(|index| index.to_le_bytes())
+                        .collect(),
+                    ComponentType::U32,
+                ),
+            };
+            let view = self.push_view(&bytes, Some(json::buffer::Target::ElementArrayBuffer));
+            self.push_accessor(json::Accessor {
+                buffer_view: Some(view),
+                byte_offset: None,
+                count: USize64::from(indices.len()),
+                component_type: Valid(GenericComponentType(component_type)),
+                extensions: None,
+                extras: None,
+                type_: Valid(Type::Scalar),
+                min: None,
+                max: None,
+                name: None,
+                normalized: false,
+                sparse: None,
+            })
+        });
+
+        json::mesh::Primitive {
+            attributes,
+            extensions: None,
+            extras: None,
+            indices,
+            material: None,
+            mode: Valid(mode),
+            targets: None,
+        }
+    }
+
+    fn write_attribute(
+        &mut self,
+        values: &VertexAttributeValues,
+        component_type: ComponentType,
+        type_: Type,
+        bounds: bool,
+    ) -> json::Index<json::Accessor> {
+        // Positions and their morph target displacements need bounds
+        let (min, max) = match values {
+            VertexAttributeValues::Float32x3(positions) if bounds => {
+                let min = positions.iter().fold(Vec3::INFINITY, |min, position| {
+                    min.min(Vec3::from(*position))
+                });
+                let max = positions.iter().fold(Vec3::NEG_INFINITY, |max, position| {
+                    max.max(Vec3::from(*position))
+                });
+                (
+                    Some(Value::from(min.to_array().to_vec())),
+                    Some(Value::from(max.to_array().to_vec())),
+                )
+            }
+            _ => (None, None),
+        };
+
+        let view = self.push_view(values.get_bytes(), Some(json::buffer::Target::ArrayBuffer));
+        self.push_accessor(json::Accessor {
+            buffer_view: Some(view),
+            byte_offset: None,
+            count: USize64::from(values.len()),
+            component_type: Valid(GenericComponentType(component_type)),
+            extensions: None,
+            extras: None,
+            type_: Valid(type_),
+            min,
+            max,
+            name: None,
+            normalized: false,
+            sparse: None,
+        })
+    }
+
+    /// The position and normal displacements of `target_count` targets for a generated
+    /// primitive. Targets the conversion couldn't remap are written as zero, every
+    /// primitive of a mesh needs the same number of them.
+    fn write_morph_targets(
+        &mut self,
+        targets: Option<&Image>,
+        vertex_count: usize,
+        target_count: usize,
+    ) -> Option<Vec<json::mesh::MorphTarget>> {
+        if target_count == 0 {
+            return None;
+        }
+        let layers = targets.map(read_morph_targets).unwrap_or_default();
+        let targets = (0..target_count)
+            .map(|target| {
+                let attributes = |vertex: usize| {
+                    layers
+                        .get(target)
+                        .and_then(|layer| layer.get(vertex))
+                        .copied()
+                        .unwrap_or_default()
+                };
+                let positions: Vec<[f32; 3]> = (0..vertex_count)
+                    .map(|vertex| attributes(vertex).position.to_array())
+                    .collect();
+                let normals: Vec<[f32; 3]> = (0..vertex_count)
+                    .map(|vertex| attributes(vertex).normal.to_array())
+                    .collect();
+                json::mesh::MorphTarget {
+                    positions: Some(self.write_attribute(
+                        &VertexAttributeValues::Float32x3(positions),
+                        ComponentType::F32,
+                        Type::Vec3,
+                        true,
+                    )),
+                    normals: Some(self.write_attribute(
+                        &VertexAttributeValues::Float32x3(normals),
+                        ComponentType::F32,
+                        Type::Vec3,
+                        false,
+                    )),
+                    tangents: None,
+                }
+            })
+            .collect();
+        Some(targets)
+    }
+
+    fn finish(self, mut root: json::Root) -> Result<Vec<u8>> {
+        let mut bin = self.bin;
+        bin.resize(bin.len().next_multiple_of(4), 0);
+
+        root.buffer_views = self.views;
+        root.accessors = self.accessors;
+        root.buffers = match bin.is_empty() {
+            true => vec![],
+            false => vec![json::Buffer {
+                byte_length: USize64::from(bin.len()),
+                name: None,
+                uri: None,
+                extensions: None,
+                extras: None,
+            }],
+        };
+
+        let json = json::serialize::to_vec(&root)?;
+        let glb = gltf::binary::Glb {
+            header: gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                length: 0,
+            },
+            json: json.into(),
+            bin: (!bin.is_empty()).then(|| bin.into()),
+        };
+        Ok(glb.to_vec()?)
+    }
+}
+
+fn accessor_format(values: &VertexAttributeValues) -> Option<(ComponentType, Type)> {
+    Some(match values {
+        VertexAttributeValues::Float32(_) => (ComponentType::F32, Type::Scalar),
+        VertexAttributeValues::Float32x2(_) => (ComponentType::F32, Type::Vec2),
+        VertexAttributeValues::Float32x3(_) => (ComponentType::F32, Type::Vec3),
+        VertexAttributeValues::Float32x4(_) => (ComponentType::F32, Type::Vec4),
+        VertexAttributeValues::Uint16x4(_) => (ComponentType::U16, Type::Vec4),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn view(offset: usize, length: usize) -> Value {
+        json!({"buffer": 0, "byteOffset": offset, "byteLength": length})
+    }
+
+    fn floats_accessor(view: usize, count: usize, type_: &str) -> Value {
+        json!({"bufferView": view, "componentType": 5126, "count": count, "type": type_})
+    }
+
+    fn vec3(view: usize, min: [f32; 3], max: [f32; 3]) -> Value {
+        let mut accessor = floats_accessor(view, 4, "VEC3");
+        accessor["min"] = json!(min);
+        accessor["max"] = json!(max);
+        accessor
+    }
+
+    /// A quad whose one morph target lifts it by one unit, animated by a weights channel.
+    fn morphing_quad() -> Value {
+        let floats = |values: &[f32]| values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let positions: Vec<u8> =
+            floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+        let normals: Vec<u8> = floats(&[0.0, 1.0, 0.0].repeat(4));
+        let indices: Vec<u8> = [0u32, 2, 1, 0, 3, 2]
+            .iter()
+            .flat_map(|index| index.to_le_bytes())
+            .collect();
+        let target: Vec<u8> = floats(&[0.0, 1.0, 0.0].repeat(4));
+        let times: Vec<u8> = floats(&[0.0, 1.0]);
+        let weights: Vec<u8> = floats(&[0.0, 1.0]);
+        let buffer = [positions, normals, indices, target, times, weights].concat();
+
+        json!({
+            "asset": {"version": "2.0"},
+            "scene": 0,
+            "scenes": [{"nodes": [0]}],
+            "nodes": [{"mesh": 0, "weights": [0.0]}],
+            "meshes": [{
+                "primitives": [{
+                    "attributes": {"POSITION": 0, "NORMAL": 1},
+                    "indices": 2,
+                    "targets": [{"POSITION": 3}],
+                }],
+                "weights": [0.0],
+            }],
+            "animations": [{
+                "channels": [{"sampler": 0, "target": {"node": 0, "path": "weights"}}],
+                "samplers": [{"input": 4, "output": 5}],
+            }],
+            "accessors": [
+                vec3(0, [0.0; 3], [1.0, 0.0, 1.0]),
+                vec3(1, [0.0, 1.0, 0.0], [0.0, 1.0, 0.0]),
+                {"bufferView": 2, "componentType": 5125, "count": 6, "type": "SCALAR"},
+                vec3(3, [0.0, 1.0, 0.0], [0.0, 1.0, 0.0]),
+                {
+                    "bufferView": 4, "componentType": 5126, "count": 2, "type": "SCALAR",
+                    "min": [0.0], "max": [1.0],
+                },
+                floats_accessor(5, 2, "SCALAR"),
+            ],
+            "bufferViews": [
+                view(0, 48),
+                view(48, 48),
+                view(96, 24),
+                view(120, 48),
+                view(168, 8),
+                view(176, 8),
+            ],
+            "buffers": [{
+                "byteLength": buffer.len(),
+                "uri": format!("data:application/octet-stream;base64,{}", STANDARD.encode(&buffer)),
+            }],
+        })
+    }
+
+    #[test]
+    fn baked_morph_targets_round_trip() {
+        let path = std::env::temp_dir().join("bevy_wireframe_morphing_quad.gltf");
+        fs::write(&path, morphing_quad().to_string()).unwrap();
+        let baked = bake_gltf(&path, &BakeOptions::default()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let gltf = gltf::Gltf::from_slice(&baked.glb).unwrap();
+        let blob = gltf.blob.as_deref();
+        let mesh = gltf.meshes().next().unwrap();
+        let modes: Vec<Mode> = mesh
+            .primitives()
+            .map(|primitive| primitive.mode())
+            .collect();
+        assert_eq!(modes, [Mode::Triangles, Mode::Lines]);
+
+        for primitive in mesh.primitives() {
+            assert_eq!(primitive.morph_targets().len(), 1);
+            let reader = primitive.reader(|_| blob);
+            let vertex_count = reader.read_positions().unwrap().len();
+            let (positions, normals, _) = reader.read_morph_targets().next().unwrap();
+            let positions: Vec<[f32; 3]> = positions.unwrap().collect();
+            assert_eq!(positions.len(), vertex_count);
+            assert!(positions
+                .iter()
+                .all(|&position| position == [0.0, 1.0, 0.0]));
+            assert_eq!(normals.unwrap().len(), vertex_count);
+        }
+
+        let animation = gltf.animations().next().unwrap();
+        assert_eq!(animation.samplers().count(), 1);
+        let channel = animation.channels().next().unwrap();
+        assert_eq!(
+            channel.target().property(),
+            gltf::animation::Property::MorphTargetWeights
+        );
+        assert_eq!(channel.sampler().input().count(), 2);
+    }
+}
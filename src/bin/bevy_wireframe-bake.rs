@@ -0,0 +1,161 @@
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        cli::main()
+    }
+    // Baking reads and writes files, there's nothing to run on the web
+    #[cfg(target_arch = "wasm32")]
+    {
+        eprintln!("bevy_wireframe-bake only runs natively");
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod cli {
+    use std::{env, fs, path::PathBuf, process::ExitCode};
+
+    use anyhow::{anyhow, Context, Result};
+    use bevy::{log::tracing_subscriber, math::Vec4};
+    use bevy_wireframe::{
+        bake::{bake_gltf, BakeOptions},
+        load_json::json_parse,
+        mesh_ops::{EdgeMode, RevealOrder},
+        vertex_colors::VertexColorStrategy,
+    };
+
+    const USAGE: &str = "\
+Converts a glTF into a .glb with the wireframe layers already built, so
+WireframePlugin only has to attach materials at runtime.
+
+Usage: bevy_wireframe-bake <INPUT> [OUTPUT] [OPTIONS]
+
+OUTPUT defaults to INPUT with a .wireframe.glb extension.
+
+Options:
+  --edges <MODE>       all, feature[:DEGREES] or quads[:DEGREES]    [default: all]
+  --colors <STRATEGY>  random[:SEED], per-mesh[:SEED], per-part[:SEED],
+                       normal, height, keep or texture            [default: random]
+  --weld <TOLERANCE>   distance under which vertices are merged    [default: 0.0001]
+  --reveal <ORDER>     index or height                             [default: index]
+  --edge-list <FILE>   JSON edge list to use instead of the sidecar next to INPUT
+  --no-sidecar         ignore the .lines.json edge list next to INPUT
+  -h, --help           print this message";
+
+    pub fn main() -> ExitCode {
+        tracing_subscriber::fmt()
+            .with_writer(std::io::stderr)
+            .init();
+
+        match run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {:#}\n\n{}", e, USAGE);
+                ExitCode::FAILURE
+            }
+        }
+    }
+
+    fn run() -> Result<()> {
+        let mut options = BakeOptions::default();
+        // Missing files are skipped here, unlike in the plugin
+        options.settings.sidecar_edge_list = true;
+        let mut paths = vec![];
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+            match arg.as_str() {
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    return Ok(());
+                }
+                "--edges" => options.settings.edge_mode = parse_edge_mode(&value()?)?,
+                "--colors" => options.settings.vertex_colors = parse_colors(&value()?)?,
+                "--weld" => options.settings.weld_tolerance = value()?.parse()?,
+                "--reveal" => {
+                    options.settings.reveal_order = match value()?.as_str() {
+                        "index" => RevealOrder::Index,
+                        "height" => RevealOrder::Height,
+                        order => return Err(anyhow!("Unknown reveal order {}", order)),
+                    }
+                }
+                "--edge-list" => {
+                    let path = value()?;
+                    let bytes =
+                        fs::read(&path).with_context(|| format!("Failed to read {}", path))?;
+                    options.edge_list = Some(json_parse(&bytes)?);
+                }
+                "--no-sidecar" => options.settings.sidecar_edge_list = false,
+                _ if arg.starts_with('-') => return Err(anyhow!("Unknown option {}", arg)),
+                _ => paths.push(PathBuf::from(arg)),
+            }
+        }
+
+        let (input, output) = match paths.as_slice() {
+            [input] => (input.clone(), input.with_extension("wireframe.glb")),
+            [input, output] => (input.clone(), output.clone()),
+            _ => return Err(anyhow!("Expected an input and an optional output path")),
+        };
+
+        let baked = bake_gltf(&input, &options)?;
+        fs::write(&output, &baked.glb)
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+        println!(
+            "Baked {} primitives with {} lines into {}",
+            baked.primitives,
+            baked.lines,
+            output.display()
+        );
+        Ok(())
+    }
+
+    /// `NAME` or `NAME:VALUE`.
+    fn split_value(arg: &str) -> (&str, Option<&str>) {
+        match arg.split_once(':') {
+            Some((name, value)) => (name, Some(value)),
+            None => (arg, None),
+        }
+    }
+
+    fn parse_edge_mode(arg: &str) -> Result<EdgeMode> {
+        let (mode, degrees) = split_value(arg);
+        let radians = |default: f32| -> Result<f32> {
+            Ok(degrees
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or(default)
+                .to_radians())
+        };
+        Ok(match mode {
+            "all" => EdgeMode::All,
+            "feature" => EdgeMode::FeatureEdges {
+                crease_angle: radians(30.0)?,
+            },
+            "quads" => EdgeMode::Quads {
+                coplanar_tolerance: radians(1.0)?,
+            },
+            _ => return Err(anyhow!("Unknown edge mode {}", arg)),
+        })
+    }
+
+    fn parse_colors(arg: &str) -> Result<VertexColorStrategy> {
+        let (strategy, seed) = split_value(arg);
+        let seed = seed.map(str::parse).transpose()?.unwrap_or(0);
+        Ok(match strategy {
+            "random" => VertexColorStrategy::Random { seed },
+            "per-mesh" => VertexColorStrategy::PerMesh { seed },
+            "per-part" => VertexColorStrategy::PerPart { seed },
+            "normal" => VertexColorStrategy::Normal,
+            "height" => VertexColorStrategy::Height {
+                low: Vec4::new(0.0, 0.2, 1.0, 1.0),
+                high: Vec4::new(1.0, 0.2, 0.6, 1.0),
+            },
+            "keep" => VertexColorStrategy::KeepOriginal,
+            "texture" => VertexColorStrategy::Texture,
+            _ => return Err(anyhow!("Unknown color strategy {}", arg)),
+        })
+    }
+}
@@ -15,6 +15,9 @@ pub struct FillMaterial {
     pub shininess: f32,
     #[uniform(0)]
     pub specular_strength: f32,
+    /// Multiplies the lit color, values above 1.0 make the fill bloom.
+    #[uniform(0)]
+    pub intensity: f32,
 }
 
 impl Default for FillMaterial {
@@ -24,6 +27,7 @@ impl Default for FillMaterial {
             displacement: 0.1,
             shininess: 200.0,
             specular_strength: 1.0,
+            intensity: 1.0,
         }
     }
 }
@@ -0,0 +1,185 @@
+use anyhow::Result;
+use bevy::{
+    ecs::system::SystemParam,
+    gltf::{GltfExtras, GltfMaterialExtras, GltfMeshExtras, GltfSceneExtras},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::WireframeSettings;
+
+/// Wireframe options read from glTF extras, i.e. Blender custom properties on scenes,
+/// objects, meshes and materials. Unset keys leave the scene's [`WireframeSettings`] alone.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct WireframeExtras {
+    /// Leave this mesh untouched.
+    pub wireframe_skip: Option<bool>,
+    /// Set by `bevy_wireframe-bake` on the primitives it has already converted.
+    pub wireframe_baked: Option<bool>,
+    /// RGB or RGBA.
+    pub line_color: Option<Vec<f32>>,
+    pub line_displacement: Option<f32>,
+    /// RGB or RGBA.
+    pub fill_color: Option<Vec<f32>>,
+    pub shininess: Option<f32>,
+    pub specular_strength: Option<f32>,
+    /// RGB or RGBA.
+    pub outline_color: Option<Vec<f32>>,
+    pub outline_width: Option<f32>,
+    /// Set per object by `assign_unique_indices.py`, selects a section of the sidecar
+    /// [`JsonLineList`](crate::load_json::JsonLineList).
+    pub gltf_primitive_index: Option<u32>,
+    /// An embedded edge list, in the same format as the `line_list` of a
+    /// [`JsonLineList`](crate::load_json::JsonLineList).
+    pub line_list: Option<Vec<[u32; 2]>>,
+}
+
+impl WireframeExtras {
+    pub fn parse(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Overrides every key of `self` that is set in `other`.
+    pub fn merge(&mut self, other: WireframeExtras) {
+        let WireframeExtras {
+            wireframe_skip,
+            wireframe_baked,
+            line_color,
+            line_displacement,
+            fill_color,
+            shininess,
+            specular_strength,
+            outline_color,
+            outline_width,
+            gltf_primitive_index,
+            line_list,
+        } = other;
+
+        self.wireframe_skip = wireframe_skip.or(self.wireframe_skip.take());
+        self.wireframe_baked = wireframe_baked.or(self.wireframe_baked.take());
+        self.line_color = line_color.or(self.line_color.take());
+        self.line_displacement = line_displacement.or(self.line_displacement.take());
+        self.fill_color = fill_color.or(self.fill_color.take());
+        self.shininess = shininess.or(self.shininess.take());
+        self.specular_strength = specular_strength.or(self.specular_strength.take());
+        self.outline_color = outline_color.or(self.outline_color.take());
+        self.outline_width = outline_width.or(self.outline_width.take());
+        self.gltf_primitive_index = gltf_primitive_index.or(self.gltf_primitive_index.take());
+        self.line_list = line_list.or(self.line_list.take());
+    }
+
+    pub fn skip(&self) -> bool {
+        self.wireframe_skip.unwrap_or(false)
+    }
+
+    pub fn baked(&self) -> bool {
+        self.wireframe_baked.unwrap_or(false)
+    }
+
+    /// Applies the styling keys to a copy of the scene's settings.
+    pub fn apply(&self, settings: &mut WireframeSettings) {
+        if let Some(color) = self.line_color.as_deref().and_then(color_from_extras) {
+            settings.line_color = Some(color);
+        }
+        if let Some(displacement) = self.line_displacement {
+            settings.line_displacement = Some(displacement);
+        }
+        if let Some(color) = self.fill_color.as_deref().and_then(color_from_extras) {
+            settings.fill_color = color;
+        }
+        if let Some(shininess) = self.shininess {
+            settings.fill_shininess = Some(shininess);
+        }
+        if let Some(specular_strength) = self.specular_strength {
+            settings.fill_specular_strength = Some(specular_strength);
+        }
+        if let Some(color) = self.outline_color.as_deref().and_then(color_from_extras) {
+            settings.outline_color = Some(color);
+        }
+        if let Some(width) = self.outline_width {
+            settings.outline_width = Some(width);
+        }
+    }
+
+    pub fn edge_list(&self) -> Option<&[[u32; 2]]> {
+        self.line_list.as_deref()
+    }
+}
+
+fn color_from_extras(values: &[f32]) -> Option<Vec4> {
+    match *values {
+        [r, g, b] => Some(Vec4::new(r, g, b, 1.0)),
+        [r, g, b, a] => Some(Vec4::new(r, g, b, a)),
+        _ => {
+            warn!("Expected an RGB or RGBA color in glTF extras, got {:?}", values);
+            None
+        }
+    }
+}
+
+pub(crate) fn parse_or_warn(json: &str) -> WireframeExtras {
+    WireframeExtras::parse(json).unwrap_or_else(|e| {
+        warn!("Ignoring glTF extras {}: {}", json, e);
+        WireframeExtras::default()
+    })
+}
+
+/// Collects the [`WireframeExtras`] that apply to a mesh entity of a spawned glTF scene.
+#[derive(SystemParam)]
+pub(crate) struct WireframeExtrasQuery<'w, 's> {
+    parents: Query<'w, 's, &'static Parent>,
+    scene_extras: Query<'w, 's, &'static GltfSceneExtras>,
+    node_extras: Query<'w, 's, &'static GltfExtras>,
+    mesh_extras: Query<'w, 's, &'static GltfMeshExtras>,
+    material_extras: Query<'w, 's, &'static GltfMaterialExtras>,
+    names: Query<'w, 's, &'static Name>,
+}
+
+impl WireframeExtrasQuery<'_, '_> {
+    /// Merges the extras from the outermost to the innermost level: scene, then the nodes
+    /// from `root` down to `entity`, then mesh, material and primitive.
+    pub(crate) fn get(&self, entity: Entity, root: Entity) -> WireframeExtras {
+        let mut ancestors = vec![];
+        let mut current = entity;
+        while current != root {
+            let Ok(parent) = self.parents.get(current) else {
+                break;
+            };
+            current = parent.get();
+            ancestors.push(current);
+        }
+
+        let mut extras = WireframeExtras::default();
+        for &ancestor in ancestors.iter().rev() {
+            if let Ok(scene) = self.scene_extras.get(ancestor) {
+                extras.merge(parse_or_warn(&scene.value));
+            }
+            if let Ok(node) = self.node_extras.get(ancestor) {
+                extras.merge(parse_or_warn(&node.value));
+            }
+        }
+        if let Ok(mesh) = self.mesh_extras.get(entity) {
+            extras.merge(parse_or_warn(&mesh.value));
+        }
+        if let Ok(material) = self.material_extras.get(entity) {
+            extras.merge(parse_or_warn(&material.value));
+        }
+        if let Ok(primitive) = self.node_extras.get(entity) {
+            extras.merge(parse_or_warn(&primitive.value));
+        }
+        extras
+    }
+
+    /// The names a sidecar edge list section can match a mesh entity by: its own `Name`
+    /// and the `Name` of the node holding it.
+    pub(crate) fn mesh_names(&self, entity: Entity) -> Vec<String> {
+        let node = self.parents.get(entity).ok().map(|parent| parent.get());
+        [Some(entity), node]
+            .into_iter()
+            .flatten()
+            .filter_map(|entity| self.names.get(entity).ok())
+            .map(|name| name.as_str().to_string())
+            .collect()
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bake;
+pub mod camera_plugin;
+pub mod fill_material;
+pub mod gltf_extras;
+pub mod line_material;
+pub mod load_json;
+pub mod mesh_cache;
+pub mod mesh_ops;
+pub mod outline_material;
+pub mod render_style;
+pub mod reveal;
+pub mod vertex_colors;
+pub mod weld;
+pub mod window_resize_plugin;
+mod wireframe_plugin;
+
+pub use wireframe_plugin::{
+    ShaderSettings, WireframeEdgeList, WireframeEdgeListSource, WireframeLayerOf, WireframeLayers,
+    WireframePlugin, WireframeProgress, WireframeScene, WireframeSettings, ATTRIBUTE_INDEX,
+    ATTRIBUTE_MATERIAL_ID, GLTF_INDEX_ATTRIBUTE, GLTF_LINE_ATTRIBUTES, GLTF_MATERIAL_ID_ATTRIBUTE,
+    HIDDEN_LINES_DIM, HIDDEN_LINES_HIDE, HIDDEN_LINES_XRAY,
+};
+
+pub mod prelude {
+    pub use crate::{
+        fill_material::FillMaterial,
+        line_material::LineMaterial,
+        mesh_ops::{EdgeClass, EdgeMode, RevealOrder},
+        outline_material::OutlineMaterial,
+        render_style::RenderStyle,
+        reveal::WireframeReveal,
+        vertex_colors::VertexColorStrategy,
+        ShaderSettings, WireframeLayers, WireframePlugin, WireframeProgress, WireframeSettings,
+    };
+}
@@ -1,17 +1,164 @@
 use bevy::{
+    pbr::{MaterialPipeline, MaterialPipelineKey},
     prelude::*,
     reflect::TypePath,
-    render::render_resource::{
-            AsBindGroup, ShaderRef,
+    render::{
+        mesh::{MeshVertexAttribute, MeshVertexBufferLayoutRef},
+        render_resource::{
+            AsBindGroup, CompareFunction, RenderPipelineDescriptor, ShaderRef,
+            SpecializedMeshPipelineError, VertexFormat,
         },
+    },
 };
 
+use crate::mesh_ops::{EdgeClass, EDGE_CLASS_COUNT};
+
+/// Normal of the first triangle adjacent to a line's edge, written by `line_list_to_mesh`.
+pub const ATTRIBUTE_FACE_NORMAL_A: MeshVertexAttribute =
+    MeshVertexAttribute::new("FaceNormalA", 988540917, VertexFormat::Float32x3);
+
+/// Normal of the second triangle adjacent to a line's edge, written by `line_list_to_mesh`.
+pub const ATTRIBUTE_FACE_NORMAL_B: MeshVertexAttribute =
+    MeshVertexAttribute::new("FaceNormalB", 988540918, VertexFormat::Float32x3);
+
+/// Other end of a line, written by `line_list_to_thick_mesh` on each corner of a line's quad.
+pub const ATTRIBUTE_LINE_OTHER_END: MeshVertexAttribute =
+    MeshVertexAttribute::new("LineOtherEnd", 988540919, VertexFormat::Float32x3);
+
+/// Which side of its line a quad corner is on (-1 or 1), and whether it is at the start (0)
+/// or the end (1) of the line.
+pub const ATTRIBUTE_LINE_SIDE: MeshVertexAttribute =
+    MeshVertexAttribute::new("LineSide", 988540920, VertexFormat::Float32x2);
+
+/// Joint indices of the other end of a line, so skinned quads follow both ends.
+pub const ATTRIBUTE_LINE_OTHER_JOINT_INDEX: MeshVertexAttribute =
+    MeshVertexAttribute::new("LineOtherJointIndex", 988540921, VertexFormat::Uint16x4);
+
+/// Joint weights of the other end of a line.
+pub const ATTRIBUTE_LINE_OTHER_JOINT_WEIGHT: MeshVertexAttribute =
+    MeshVertexAttribute::new("LineOtherJointWeight", 988540922, VertexFormat::Float32x4);
+
+/// Distance of a line's end from its start along the line, 0 at the start. Used to lay out
+/// dash patterns.
+pub const ATTRIBUTE_LINE_DISTANCE: MeshVertexAttribute =
+    MeshVertexAttribute::new("LineDistance", 988540923, VertexFormat::Float32);
+
+/// When a line starts growing in a reveal (0 to 1), its length, and whether it grows from its
+/// end (1) rather than its start (0). Written by `insert_reveal_order`.
+pub const ATTRIBUTE_LINE_REVEAL: MeshVertexAttribute =
+    MeshVertexAttribute::new("LineReveal", 988540924, VertexFormat::Float32x3);
+
+/// The [`EdgeClass`] of the edge a line was extracted from, as a float so it can be baked
+/// into glTF. Indexes [`LineMaterial::edge_class_colors`].
+pub const ATTRIBUTE_LINE_EDGE_CLASS: MeshVertexAttribute =
+    MeshVertexAttribute::new("LineEdgeClass", 988540925, VertexFormat::Float32);
+
+/// Thick lines end flush with their endpoints.
+pub const LINE_CAP_BUTT: i32 = 0;
+/// Thick lines extend half their width past their endpoints.
+pub const LINE_CAP_SQUARE: i32 = 1;
+/// Thick lines end in a half circle. Where lines meet this also gives round joins.
+pub const LINE_CAP_ROUND: i32 = 2;
+
+/// Unbroken lines.
+pub const DASH_SOLID: i32 = 0;
+/// Dashes of `dash_length` separated by `gap_length`.
+pub const DASH_DASHED: i32 = 1;
+/// Dots a fifth of `dash_length` long separated by `gap_length`.
+pub const DASH_DOTTED: i32 = 2;
+/// A dash, a gap, a dot and another gap.
+pub const DASH_DASH_DOT: i32 = 3;
+
+/// Lines are hidden behind the surfaces in front of them.
+pub const DEPTH_TEST_VISIBLE: i32 = 0;
+/// Lines are drawn through everything, x-ray style.
+pub const DEPTH_TEST_ALWAYS: i32 = 1;
+/// Only the parts of lines hidden behind other surfaces are drawn, for a second pass that
+/// shows hidden edges in their own style.
+pub const DEPTH_TEST_OCCLUDED: i32 = 2;
+
+/// Draw every edge.
+pub const SILHOUETTE_OFF: i32 = 0;
+/// Only draw edges between a front-facing and a back-facing triangle.
+pub const SILHOUETTE_ONLY: i32 = 1;
+/// Draw non-silhouette edges with their alpha scaled by `silhouette_fade`.
+pub const SILHOUETTE_FADE: i32 = 2;
+
 #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+#[bind_group_data(LineMaterialKey)]
 pub struct LineMaterial {
     #[uniform(0)]
     pub color: Vec4,
     #[uniform(0)]
     pub displacement: f32,
+    #[uniform(0)]
+    pub use_vertex_color: i32,
+    /// One of [`SILHOUETTE_OFF`], [`SILHOUETTE_ONLY`] or [`SILHOUETTE_FADE`].
+    #[uniform(0)]
+    pub silhouette_mode: i32,
+    #[uniform(0)]
+    pub silhouette_fade: f32,
+    /// Width of thick lines, in pixels or world units. Only used with meshes built by
+    /// `line_list_to_thick_mesh`, plain line lists are always one pixel wide.
+    #[uniform(0)]
+    pub width: f32,
+    #[uniform(0)]
+    pub width_in_world_units: i32,
+    /// One of [`LINE_CAP_BUTT`], [`LINE_CAP_SQUARE`] or [`LINE_CAP_ROUND`].
+    #[uniform(0)]
+    pub line_cap: i32,
+    /// One of [`DASH_SOLID`], [`DASH_DASHED`], [`DASH_DOTTED`] or [`DASH_DASH_DOT`]. The
+    /// lengths are in the same units as the mesh.
+    #[uniform(0)]
+    pub dash_pattern: i32,
+    #[uniform(0)]
+    pub dash_length: f32,
+    #[uniform(0)]
+    pub gap_length: f32,
+    /// Shifts the pattern along each line.
+    #[uniform(0)]
+    pub dash_offset: f32,
+    /// Moves the pattern along each line by this much per second, for marching ants.
+    #[uniform(0)]
+    pub dash_speed: f32,
+    /// View distance at which lines start fading towards `fog_color`.
+    #[uniform(0)]
+    pub fog_near: f32,
+    /// View distance at which lines are fully faded. Fog is off when not beyond `fog_near`.
+    #[uniform(0)]
+    pub fog_far: f32,
+    /// The alpha is how much of the fog color distant lines take on.
+    #[uniform(0)]
+    pub fog_color: Vec4,
+    /// How much of a reveal has played, see [`crate::reveal::WireframeReveal`]. 1.0 shows
+    /// every line.
+    #[uniform(0)]
+    pub reveal_progress: f32,
+    #[uniform(0)]
+    pub reveal_spread: f32,
+    /// Multiplies the color, values above 1.0 make the lines bloom.
+    #[uniform(0)]
+    pub intensity: f32,
+    /// Multiplies the color of the lines of each [`EdgeClass`], indexed by the class. An
+    /// alpha below 1.0 blends the lines, 0.0 hides them. Only used with meshes carrying
+    /// [`ATTRIBUTE_LINE_EDGE_CLASS`].
+    #[uniform(0)]
+    pub edge_class_colors: [Vec4; EDGE_CLASS_COUNT],
+    /// One of [`DEPTH_TEST_VISIBLE`], [`DEPTH_TEST_ALWAYS`] or [`DEPTH_TEST_OCCLUDED`].
+    pub depth_test: i32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LineMaterialKey {
+    depth_test: i32,
+}
+
+impl From<&LineMaterial> for LineMaterialKey {
+    fn from(material: &LineMaterial) -> Self {
+        Self {
+            depth_test: material.depth_test,
+        }
+    }
 }
 
 
@@ -20,10 +167,41 @@ impl Default for LineMaterial {
         Self {
             color: Vec4::new(1.0, 0.3, 1.0, 1.0),
             displacement: 0.0,
+            use_vertex_color: 1,
+            silhouette_mode: SILHOUETTE_OFF,
+            silhouette_fade: 0.15,
+            width: 2.0,
+            width_in_world_units: 0,
+            line_cap: LINE_CAP_ROUND,
+            dash_pattern: DASH_SOLID,
+            dash_length: 0.1,
+            gap_length: 0.05,
+            dash_offset: 0.0,
+            dash_speed: 0.0,
+            fog_near: 0.0,
+            fog_far: 0.0,
+            fog_color: Vec4::new(0.0, 0.0, 0.0, 1.0),
+            reveal_progress: 1.0,
+            reveal_spread: 0.1,
+            intensity: 1.0,
+            edge_class_colors: [Vec4::ONE; EDGE_CLASS_COUNT],
+            depth_test: DEPTH_TEST_VISIBLE,
         }
     }
 }
 
+impl LineMaterial {
+    /// The tint of the lines of `class`.
+    pub fn edge_class_color(&self, class: EdgeClass) -> Vec4 {
+        self.edge_class_colors[class as usize]
+    }
+
+    /// Tints the lines of `class`, see [`LineMaterial::edge_class_colors`].
+    pub fn set_edge_class_color(&mut self, class: EdgeClass, color: Vec4) {
+        self.edge_class_colors[class as usize] = color;
+    }
+}
+
 impl Material for LineMaterial {
 
     fn vertex_shader() -> ShaderRef {
@@ -33,6 +211,116 @@ impl Material for LineMaterial {
     fn fragment_shader() -> ShaderRef {
         "shaders/line.wgsl".into()
     }
-}
 
+    fn alpha_mode(&self) -> AlphaMode {
+        // Lines that ignore or invert the depth test are drawn after the opaque pass, so the
+        // fill can't cover them again
+        let faded_class = self
+            .edge_class_colors
+            .iter()
+            .any(|color| color.w > 0.0 && color.w < 1.0);
+        if self.silhouette_mode == SILHOUETTE_FADE
+            || faded_class
+            || self.depth_test != DEPTH_TEST_VISIBLE
+        {
+            AlphaMode::Blend
+        } else {
+            AlphaMode::Opaque
+        }
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // Line meshes carry attributes the default mesh layout doesn't know about, so the
+        // vertex buffer is laid out to match `LineVertex` in line.wgsl
+        let mesh_layout = &layout.0;
+        let mut attributes = vec![Mesh::ATTRIBUTE_POSITION.at_shader_location(0)];
+        if mesh_layout.contains(Mesh::ATTRIBUTE_NORMAL) {
+            attributes.push(Mesh::ATTRIBUTE_NORMAL.at_shader_location(1));
+        }
+        if mesh_layout.contains(Mesh::ATTRIBUTE_COLOR) {
+            attributes.push(Mesh::ATTRIBUTE_COLOR.at_shader_location(5));
+        }
+        if mesh_layout.contains(Mesh::ATTRIBUTE_JOINT_INDEX)
+            && mesh_layout.contains(Mesh::ATTRIBUTE_JOINT_WEIGHT)
+        {
+            attributes.push(Mesh::ATTRIBUTE_JOINT_INDEX.at_shader_location(6));
+            attributes.push(Mesh::ATTRIBUTE_JOINT_WEIGHT.at_shader_location(7));
+        }
+
+        let defs = &mut descriptor.vertex.shader_defs;
+        if mesh_layout.contains(ATTRIBUTE_FACE_NORMAL_A)
+            && mesh_layout.contains(ATTRIBUTE_FACE_NORMAL_B)
+        {
+            attributes.push(ATTRIBUTE_FACE_NORMAL_A.at_shader_location(8));
+            attributes.push(ATTRIBUTE_FACE_NORMAL_B.at_shader_location(9));
+            defs.push("LINE_FACE_NORMALS".into());
+        }
+
+        if mesh_layout.contains(ATTRIBUTE_LINE_OTHER_END)
+            && mesh_layout.contains(ATTRIBUTE_LINE_SIDE)
+        {
+            attributes.push(ATTRIBUTE_LINE_OTHER_END.at_shader_location(10));
+            attributes.push(ATTRIBUTE_LINE_SIDE.at_shader_location(11));
+            if mesh_layout.contains(ATTRIBUTE_LINE_OTHER_JOINT_INDEX)
+                && mesh_layout.contains(ATTRIBUTE_LINE_OTHER_JOINT_WEIGHT)
+            {
+                attributes.push(ATTRIBUTE_LINE_OTHER_JOINT_INDEX.at_shader_location(12));
+                attributes.push(ATTRIBUTE_LINE_OTHER_JOINT_WEIGHT.at_shader_location(13));
+            }
+            defs.push("THICK_LINES".into());
+            if let Some(fragment) = descriptor.fragment.as_mut() {
+                fragment.shader_defs.push("THICK_LINES".into());
+            }
+            // The quads are expanded towards the camera, whichever way they wind
+            descriptor.primitive.cull_mode = None;
+        }
+
+        if mesh_layout.contains(ATTRIBUTE_LINE_DISTANCE) {
+            attributes.push(ATTRIBUTE_LINE_DISTANCE.at_shader_location(14));
+            descriptor.vertex.shader_defs.push("LINE_DISTANCE".into());
+            if let Some(fragment) = descriptor.fragment.as_mut() {
+                fragment.shader_defs.push("LINE_DISTANCE".into());
+            }
+
+            if mesh_layout.contains(ATTRIBUTE_LINE_REVEAL) {
+                attributes.push(ATTRIBUTE_LINE_REVEAL.at_shader_location(15));
+                descriptor.vertex.shader_defs.push("LINE_REVEAL".into());
+                if let Some(fragment) = descriptor.fragment.as_mut() {
+                    fragment.shader_defs.push("LINE_REVEAL".into());
+                }
+            }
+        }
+
+        if mesh_layout.contains(ATTRIBUTE_LINE_EDGE_CLASS) {
+            attributes.push(ATTRIBUTE_LINE_EDGE_CLASS.at_shader_location(2));
+            descriptor.vertex.shader_defs.push("LINE_EDGE_CLASS".into());
+            if let Some(fragment) = descriptor.fragment.as_mut() {
+                fragment.shader_defs.push("LINE_EDGE_CLASS".into());
+            }
+        }
+
+        descriptor.vertex.buffers = vec![mesh_layout.get_layout(&attributes)?];
 
+        if let Some(depth_stencil) = descriptor.depth_stencil.as_mut() {
+            // Bevy uses reverse-z, so a fragment behind the depth buffer has a smaller depth
+            match key.bind_group_data.depth_test {
+                DEPTH_TEST_ALWAYS => {
+                    depth_stencil.depth_compare = CompareFunction::Always;
+                    depth_stencil.depth_write_enabled = false;
+                }
+                DEPTH_TEST_OCCLUDED => {
+                    depth_stencil.depth_compare = CompareFunction::Less;
+                    depth_stencil.depth_write_enabled = false;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
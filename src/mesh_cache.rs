@@ -0,0 +1,212 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, Weak},
+};
+
+use bevy::{asset::StrongHandle, prelude::*, utils::HashMap};
+
+use crate::WireframeSettings;
+
+/// The meshes generated from one source mesh. The handles are reserved when the conversion
+/// starts and their assets are added once it's done.
+#[derive(Clone, Debug)]
+pub struct WireframeMeshes {
+    /// Flat shaded copy of the source mesh, for the fill and outline layers.
+    pub flat_mesh: Handle<Mesh>,
+    pub line_mesh: Option<Handle<Mesh>>,
+    /// The source mesh before line conversion, kept for rebuilding the lines from a
+    /// hot-reloaded edge list.
+    pub source_mesh: Option<Handle<Mesh>>,
+    /// Whether the source mesh has morph targets, so the line entities need its weights.
+    /// The line mesh itself can't be checked, it leaves the main world once extracted.
+    pub morphed: bool,
+}
+
+/// A source mesh and everything else its conversion depends on.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct WireframeMeshKey {
+    pub mesh: AssetId<Mesh>,
+    /// Hash of the settings, material, edge list and sibling primitives the meshes were
+    /// generated with.
+    pub options: u64,
+}
+
+impl WireframeMeshKey {
+    pub(crate) fn new(
+        mesh: AssetId<Mesh>,
+        settings: &WireframeSettings,
+        mesh_key: u64,
+        material: Option<AssetId<StandardMaterial>>,
+        edge_list: Option<&[[u32; 2]]>,
+        keep_source: bool,
+        material_seams: &[Handle<Mesh>],
+    ) -> Self {
+        let mut hasher = DefaultHasher::new();
+        // The float heavy settings are hashed through their Debug output
+        format!(
+            "{:?} {:?} {:?}",
+            settings.vertex_colors, settings.edge_mode, settings.reveal_order
+        )
+        .hash(&mut hasher);
+        settings.weld_tolerance.to_bits().hash(&mut hasher);
+        (settings.line_width > 0.0).hash(&mut hasher);
+        settings.layers.line.hash(&mut hasher);
+        mesh_key.hash(&mut hasher);
+        material.hash(&mut hasher);
+        edge_list.hash(&mut hasher);
+        keep_source.hash(&mut hasher);
+        for seam in material_seams {
+            seam.id().hash(&mut hasher);
+        }
+
+        Self {
+            mesh,
+            options: hasher.finish(),
+        }
+    }
+}
+
+/// [`WireframeMeshes`] that don't keep their meshes alive. The line meshes only live in the
+/// render world, so their handles are upgraded directly rather than through `Assets<Mesh>`.
+struct WeakMeshes {
+    flat_mesh: Weak<StrongHandle>,
+    line_mesh: Option<Weak<StrongHandle>>,
+    source_mesh: Option<Weak<StrongHandle>>,
+    morphed: bool,
+}
+
+impl WeakMeshes {
+    fn new(meshes: &WireframeMeshes) -> Option<Self> {
+        let downgrade = |handle: &Handle<Mesh>| match handle {
+            Handle::Strong(handle) => Some(Arc::downgrade(handle)),
+            Handle::Weak(_) => None,
+        };
+        Some(Self {
+            flat_mesh: downgrade(&meshes.flat_mesh)?,
+            line_mesh: meshes.line_mesh.as_ref().and_then(downgrade),
+            source_mesh: meshes.source_mesh.as_ref().and_then(downgrade),
+            morphed: meshes.morphed,
+        })
+    }
+
+    /// `None` once any of the meshes has been dropped by everything using it.
+    fn upgrade(&self) -> Option<WireframeMeshes> {
+        let upgrade = |handle: &Weak<StrongHandle>| handle.upgrade().map(Handle::Strong);
+        Some(WireframeMeshes {
+            flat_mesh: upgrade(&self.flat_mesh)?,
+            line_mesh: match &self.line_mesh {
+                Some(line_mesh) => Some(upgrade(line_mesh)?),
+                None => None,
+            },
+            source_mesh: match &self.source_mesh {
+                Some(source_mesh) => Some(upgrade(source_mesh)?),
+                None => None,
+            },
+            morphed: self.morphed,
+        })
+    }
+
+    fn is_alive(&self) -> bool {
+        [
+            Some(&self.flat_mesh),
+            self.line_mesh.as_ref(),
+            self.source_mesh.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .all(|handle| handle.strong_count() > 0)
+    }
+}
+
+/// Generated meshes shared by every instance of a source mesh converted with the same
+/// options, so five copies of a scene only convert and upload each mesh once.
+///
+/// The cache doesn't keep the meshes alive. Entries are dropped once the entities using
+/// their meshes are gone, or when their source mesh is modified or removed.
+#[derive(Resource, Default)]
+pub struct WireframeMeshCache {
+    entries: HashMap<WireframeMeshKey, WeakMeshes>,
+}
+
+impl WireframeMeshCache {
+    pub fn get(&self, key: &WireframeMeshKey) -> Option<WireframeMeshes> {
+        self.entries.get(key).and_then(WeakMeshes::upgrade)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forgets all generated meshes, the next instances are converted again.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Forgets the meshes generated from `mesh`.
+    pub fn invalidate(&mut self, mesh: AssetId<Mesh>) {
+        self.entries.retain(|key, _| key.mesh != mesh);
+    }
+
+    /// Forgets the entries whose meshes have been dropped.
+    pub fn evict_unused(&mut self) {
+        self.entries.retain(|_, meshes| meshes.is_alive());
+    }
+
+    pub(crate) fn insert(&mut self, key: WireframeMeshKey, meshes: &WireframeMeshes) {
+        if let Some(meshes) = WeakMeshes::new(meshes) {
+            self.entries.insert(key, meshes);
+        }
+    }
+}
+
+pub(crate) fn invalidate_mesh_cache(
+    mut events: EventReader<AssetEvent<Mesh>>,
+    mut cache: ResMut<WireframeMeshCache>,
+) {
+    let mut unused = false;
+    for event in events.read() {
+        match event {
+            AssetEvent::Modified { id } | AssetEvent::Removed { id } => cache.invalidate(*id),
+            AssetEvent::Unused { .. } => unused = true,
+            _ => {}
+        }
+    }
+    if unused {
+        cache.evict_unused();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_evicted_once_their_meshes_are_dropped() {
+        let mut assets = Assets::<Mesh>::default();
+        let key = WireframeMeshKey {
+            mesh: AssetId::default(),
+            options: 0,
+        };
+        let meshes = WireframeMeshes {
+            flat_mesh: assets.add(Cuboid::default()),
+            line_mesh: Some(assets.add(Cuboid::default())),
+            source_mesh: None,
+            morphed: false,
+        };
+
+        let mut cache = WireframeMeshCache::default();
+        cache.insert(key, &meshes);
+        assert!(cache.get(&key).is_some());
+        cache.evict_unused();
+        assert_eq!(cache.len(), 1);
+
+        drop(meshes);
+        assert!(cache.get(&key).is_none());
+        cache.evict_unused();
+        assert!(cache.is_empty());
+    }
+}
@@ -1,122 +1,385 @@
+use std::collections::VecDeque;
+
 use anyhow::{anyhow, Result};
 
 use bevy::{
     math::Vec3,
-    prelude::Mesh,
+    prelude::{Handle, Image, Mesh},
+    reflect::Struct,
     render::{
-        mesh::{Indices, MeshVertexAttribute, VertexAttributeValues},
+        mesh::{
+            morph::{MorphAttributes, MorphTargetImage},
+            Indices, VertexAttributeValues,
+        },
         render_asset::RenderAssetUsages,
+        render_resource::PrimitiveTopology,
     },
     utils::{HashMap, HashSet},
 };
 
-use rand::Rng;
 use tracing::{info, warn};
 
 use crate::{
-    load_json::{json_parse, JsonLineList},
-    WireframeSettings, ATTRIBUTE_INDEX,
+    line_material::{
+        ATTRIBUTE_FACE_NORMAL_A, ATTRIBUTE_FACE_NORMAL_B, ATTRIBUTE_LINE_DISTANCE,
+        ATTRIBUTE_LINE_EDGE_CLASS, ATTRIBUTE_LINE_OTHER_END, ATTRIBUTE_LINE_OTHER_JOINT_INDEX,
+        ATTRIBUTE_LINE_OTHER_JOINT_WEIGHT, ATTRIBUTE_LINE_REVEAL, ATTRIBUTE_LINE_SIDE,
+    },
+    weld::{weld_positions, Weld, DEFAULT_WELD_TOLERANCE},
+    WireframeSettings, ATTRIBUTE_INDEX, ATTRIBUTE_MATERIAL_ID,
 };
 
-pub fn mesh_to_wireframe(mesh: &mut Mesh, settings: &WireframeSettings) -> Result<()> {
-    // apply_random_vertex_colors(mesh);
-
-    // let line_list = if let Some(_) = &settings.gltf_path {
-    //     // Only call json_parse if gltf_path is Some
-    //     match json_parse(settings) {
-    //         Ok(parsed_data) => mesh.mesh_to_line_list_custom(parsed_data),
-    //         Err(e) => panic!("Failed to parse JSON: {}", e),
-    //         // Err(e) => return Err(anyhow::anyhow!("Failed to parse JSON: {}", e)),
-    //     }
-    // } else {
-    //     info!("No JSON file provided, computing line list from mesh vertices");
-    //     mesh.mesh_to_line_list()
-    // };
-    
-    let line_list = mesh.mesh_to_line_list();
-
-    let line_mesh = line_list_to_mesh(&line_list, mesh);
+/// Replaces `mesh` with its line mesh and returns the lines it was built from. A custom
+/// `edge_list` is used when given and the mesh carries the `INDEX` attribute it refers to,
+/// otherwise the edges come from the triangles.
+pub fn mesh_to_wireframe(
+    mesh: &mut Mesh,
+    settings: &WireframeSettings,
+    edge_list: Option<&[[u32; 2]]>,
+) -> Result<LineList> {
+    let line_list = match edge_list {
+        Some(edge_list) if mesh.attribute(ATTRIBUTE_INDEX).is_some() => {
+            info!("Using custom edge list with {} lines", edge_list.len());
+            mesh.mesh_to_line_list_custom(edge_list, settings.weld_tolerance)
+        }
+        _ => mesh_to_line_list(mesh, settings.edge_mode, settings.weld_tolerance)?,
+    };
+
+    let line_mesh = if settings.line_width > 0.0 {
+        line_list_to_thick_mesh(&line_list, mesh)
+    } else {
+        line_list_to_mesh(&line_list, mesh)
+    };
     *mesh = line_mesh;
+    if settings.reveal_order != RevealOrder::Index {
+        insert_reveal_order(mesh, &line_list, settings.reveal_order);
+    }
 
-    Ok(())
+    Ok(line_list)
+}
+
+#[derive(Clone, Default)]
+pub struct LineList {
+    pub lines: Vec<(Vert, Vert)>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Vert {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub color: Option<[f32; 4]>,
+    pub joint_indices: Option<[u16; 4]>,
+    pub joint_weights: Option<[f32; 4]>,
+    /// Normals of the two triangles sharing the edge, see [`ATTRIBUTE_FACE_NORMAL_A`].
+    pub face_normals: Option<[[f32; 3]; 2]>,
+    /// The vertex of the source mesh this end was copied from, for remapping its morph
+    /// targets with [`line_source_vertices`].
+    pub source_vertex: Option<u32>,
+    /// What kind of edge the line was extracted from, the same at both ends.
+    pub edge_class: EdgeClass,
 }
 
-pub trait RandomizeVertexColors {
-    fn randomize_vertex_colors(&mut self);
+/// Which triangle edges `mesh_to_line_list` turns into lines.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EdgeMode {
+    /// Every triangle edge.
+    #[default]
+    All,
+    /// Boundary and non-manifold edges, plus edges whose dihedral angle (in radians) is
+    /// above `crease_angle`.
+    FeatureEdges { crease_angle: f32 },
+    /// Every triangle edge except the diagonals between pairs of triangles that form a
+    /// planar quad. Triangles are paired when their normals differ by at most
+    /// `coplanar_tolerance` radians.
+    Quads { coplanar_tolerance: f32 },
 }
 
-impl RandomizeVertexColors for Mesh {
-    fn randomize_vertex_colors(&mut self) {
-        apply_random_vertex_colors(self);
+impl EdgeMode {
+    /// The crease angle and coplanar tolerance edges are classified with, the mode's own
+    /// where it has one.
+    fn classification_angles(self) -> (f32, f32) {
+        match self {
+            EdgeMode::All => (DEFAULT_CREASE_ANGLE, DEFAULT_COPLANAR_TOLERANCE),
+            EdgeMode::FeatureEdges { crease_angle } => (crease_angle, DEFAULT_COPLANAR_TOLERANCE),
+            EdgeMode::Quads { coplanar_tolerance } => (DEFAULT_CREASE_ANGLE, coplanar_tolerance),
+        }
     }
 }
 
-fn apply_random_vertex_colors(mesh: &mut Mesh) {
-    let mut rng: rand::prelude::ThreadRng = rand::thread_rng();
-    let mut unique_positions: Vec<([f32; 3], [f32; 4])> = Vec::new();
+/// Dihedral angle, in radians, above which an edge is an [`EdgeClass::Crease`] unless
+/// [`EdgeMode::FeatureEdges`] sets its own.
+pub const DEFAULT_CREASE_ANGLE: f32 = std::f32::consts::FRAC_PI_6;
 
-    let mult: f32 = 20.0;
+/// How far apart the normals of two triangles may be, in radians, for the edge between them
+/// to be an [`EdgeClass::Diagonal`] unless [`EdgeMode::Quads`] sets its own.
+pub const DEFAULT_COPLANAR_TOLERANCE: f32 = std::f32::consts::PI / 180.0;
 
-    if let Some(VertexAttributeValues::Float32x3(positions)) =
-        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
-    {
-        let colors: Vec<[f32; 4]> = positions
+/// Number of [`EdgeClass`] variants, the length of
+/// [`crate::line_material::LineMaterial::edge_class_colors`].
+pub const EDGE_CLASS_COUNT: usize = 6;
+
+/// What kind of mesh edge a line was extracted from, written to the line mesh as
+/// [`ATTRIBUTE_LINE_EDGE_CLASS`] so each kind can be styled on its own. When several apply,
+/// the first in the order below wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EdgeClass {
+    /// Edge of a single triangle, shared by more than two, or not part of any.
+    Boundary = 0,
+    /// The adjacent triangles have a different [`ATTRIBUTE_MATERIAL_ID`]. Meshes split into a
+    /// primitive per material, as glTF meshes are, get these along the boundaries they
+    /// share instead, see [`mark_material_boundaries`].
+    MaterialBoundary = 1,
+    /// The adjacent faces meet at more than the crease angle, see [`DEFAULT_CREASE_ANGLE`].
+    Crease = 2,
+    /// The adjacent triangles have different UVs along the edge.
+    UvSeam = 3,
+    /// Diagonal of a planar quad split into two triangles.
+    Diagonal = 4,
+    /// Any other edge between two triangles.
+    #[default]
+    Interior = 5,
+}
+
+/// The order in which a [`crate::reveal::WireframeReveal`] draws the lines on. Positions are
+/// in the mesh's local space.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RevealOrder {
+    /// The order the lines were extracted in.
+    #[default]
+    Index,
+    /// From the bottom up.
+    Height,
+    /// Outwards from a point.
+    DistanceFrom(Vec3),
+    /// Outwards along the lines from the line end closest to `seed`.
+    GraphDistance { seed: Vec3 },
+}
+
+impl LineList {
+    /// When each line starts growing in a reveal, from 0.0 for the first lines to 1.0 for the
+    /// last, and whether it grows from its end rather than its start.
+    pub fn reveal_order(&self, order: RevealOrder) -> Vec<(f32, bool)> {
+        // Every line end gets a key, a line starts at its lowest key and grows from that end
+        let end_keys: Vec<(f32, f32)> = match order {
+            RevealOrder::Index => (0..self.lines.len())
+                .map(|i| (i as f32, i as f32))
+                .collect(),
+            RevealOrder::Height => self
+                .lines
+                .iter()
+                .map(|(start, end)| (start.position[1], end.position[1]))
+                .collect(),
+            RevealOrder::DistanceFrom(point) => self
+                .lines
+                .iter()
+                .map(|(start, end)| {
+                    (
+                        Vec3::from(start.position).distance(point),
+                        Vec3::from(end.position).distance(point),
+                    )
+                })
+                .collect(),
+            RevealOrder::GraphDistance { seed } => graph_distances(self, seed),
+        };
+
+        let line_keys: Vec<f32> = end_keys
             .iter()
-            .map(|&pos| {
-                match unique_positions
-                    .iter()
-                    .position(|&(p, _)| vec3_approx_eq(p, pos))
-                {
-                    Some(index) => unique_positions[index].1,
-                    None => {
-                        let color = [
-                            rng.gen::<f32>() * mult,
-                            rng.gen::<f32>() * mult,
-                            rng.gen::<f32>() * mult,
-                            1.0,
-                        ];
-
-                        unique_positions.push((pos, color));
-                        color
-                    }
-                }
-            })
+            .map(|(start, end)| start.min(*end))
             .collect();
+        let min = line_keys.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = line_keys.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let range = max - min;
 
-        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        line_keys
+            .iter()
+            .zip(&end_keys)
+            .map(|(key, (start, end))| {
+                let order = if range > f32::EPSILON {
+                    (key - min) / range
+                } else {
+                    0.0
+                };
+                (order, end < start)
+            })
+            .collect()
     }
 }
 
-fn vec3_approx_eq(a: [f32; 3], b: [f32; 3]) -> bool {
-    const EPSILON: f32 = 1e-5;
-    (a[0] - b[0]).abs() < EPSILON && (a[1] - b[1]).abs() < EPSILON && (a[2] - b[2]).abs() < EPSILON
+/// Hops along the lines from the line end closest to `seed`, for each end of each line.
+/// Ends that can't be reached come after all others.
+fn graph_distances(line_list: &LineList, seed: Vec3) -> Vec<(f32, f32)> {
+    // Line ends are welded so lines meeting at a vertex are connected
+    let ends: Vec<[f32; 3]> = line_list
+        .lines
+        .iter()
+        .flat_map(|(start, end)| [start.position, end.position])
+        .collect();
+    let weld = weld_positions(&ends, DEFAULT_WELD_TOLERANCE);
+
+    let mut neighbours: Vec<Vec<u32>> = vec![Vec::new(); weld.len()];
+    for line in 0..line_list.lines.len() {
+        let (a, b) = (weld.id(line * 2), weld.id(line * 2 + 1));
+        neighbours[a as usize].push(b);
+        neighbours[b as usize].push(a);
+    }
+
+    let seed_id = ends
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            let a = Vec3::from(**a).distance_squared(seed);
+            let b = Vec3::from(**b).distance_squared(seed);
+            a.total_cmp(&b)
+        })
+        .map(|(index, _)| weld.id(index));
+
+    let mut hops: Vec<Option<u32>> = vec![None; weld.len()];
+    let mut queue = VecDeque::new();
+    if let Some(seed_id) = seed_id {
+        hops[seed_id as usize] = Some(0);
+        queue.push_back(seed_id);
+    }
+    while let Some(id) = queue.pop_front() {
+        let next = hops[id as usize].unwrap_or(0) + 1;
+        for &neighbour in &neighbours[id as usize] {
+            if hops[neighbour as usize].is_none() {
+                hops[neighbour as usize] = Some(next);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    let unreachable = hops.iter().flatten().max().map_or(0, |max| max + 1);
+    let hops_to = |end: usize| hops[weld.id(end) as usize].unwrap_or(unreachable) as f32;
+    (0..line_list.lines.len())
+        .map(|line| (hops_to(line * 2), hops_to(line * 2 + 1)))
+        .collect()
 }
 
-#[derive(Clone, Default)]
-pub struct LineList {
-    pub lines: Vec<(Vert, Vert)>,
+/// Writes [`ATTRIBUTE_LINE_REVEAL`] for a mesh built from `line_list` by
+/// [`line_list_to_mesh`] or [`line_list_to_thick_mesh`].
+pub fn insert_reveal_order(line_mesh: &mut Mesh, line_list: &LineList, order: RevealOrder) {
+    let vertex_count = line_mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)
+        .map_or(0, |positions| positions.len());
+    let vertices_per_line = vertex_count / line_list.lines.len().max(1);
+    let reveal: Vec<[f32; 3]> = line_list
+        .lines
+        .iter()
+        .zip(line_list.reveal_order(order))
+        .flat_map(|((start, end), (order, from_end))| {
+            let value = [order, line_length(start, end), from_end as u32 as f32];
+            vec![value; vertices_per_line]
+        })
+        .collect();
+    line_mesh.insert_attribute(ATTRIBUTE_LINE_REVEAL, reveal);
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct Vert {
-    pub position: [f32; 3],
-    pub normal: [f32; 3],
-    pub color: Option<[f32; 4]>,
-    pub joint_indices: Option<[u16; 4]>,
-    pub joint_weights: Option<[f32; 4]>,
+/// The source vertex of every vertex of a mesh built from `line_list` by
+/// [`line_list_to_mesh`], or by [`line_list_to_thick_mesh`] when `thick`. `None` when a line
+/// wasn't built from a mesh.
+pub fn line_source_vertices(line_list: &LineList, thick: bool) -> Option<Vec<u32>> {
+    let vertices_per_end = if thick { 2 } else { 1 };
+    line_list
+        .lines
+        .iter()
+        .flat_map(|(start, end)| {
+            [start.source_vertex; 2]
+                .into_iter()
+                .take(vertices_per_end)
+                .chain([end.source_vertex; 2].into_iter().take(vertices_per_end))
+        })
+        .collect()
 }
 
-pub trait mesh_to_line_list {
-    fn mesh_to_line_list(&self) -> LineList;
+/// The morph target image of `mesh`, which Bevy only exposes through reflection.
+pub fn morph_targets(mesh: &Mesh) -> Option<&Handle<Image>> {
+    mesh.field("morph_targets")?
+        .downcast_ref::<Option<Handle<Image>>>()?
+        .as_ref()
 }
 
-impl mesh_to_line_list for Mesh {
-    fn mesh_to_line_list(&self) -> LineList {
-        match mesh_to_line_list(self) {
-            Ok(line_list) => line_list,
-            Err(e) => panic!("Failed to convert mesh to line list: {}", e),
-        }
+/// The attributes of each vertex in each target of a morph target image, the inverse of
+/// [`MorphTargetImage::new`]. Layers are padded, so they can hold more vertices than the mesh.
+pub fn read_morph_targets(targets: &Image) -> Vec<Vec<MorphAttributes>> {
+    let size = targets.texture_descriptor.size;
+    // Each layer holds one target, padded to fill its width × height
+    let layer_len = (size.width * size.height) as usize;
+    let components: Vec<f32> = targets
+        .data
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+
+    components
+        .chunks_exact(layer_len.max(1))
+        .take(size.depth_or_array_layers as usize)
+        .map(|layer| {
+            layer
+                .chunks_exact(MorphAttributes::COMPONENT_COUNT)
+                .map(|c| MorphAttributes {
+                    position: Vec3::from_slice(&c[0..3]),
+                    normal: Vec3::from_slice(&c[3..6]),
+                    tangent: Vec3::from_slice(&c[6..9]),
+                })
+                .collect()
+        })
+        .collect()
+}
 
+/// Builds the morph target image of a mesh whose vertex `i` is a copy of vertex
+/// `source_vertices[i]` of the mesh `targets` belongs to.
+pub fn remap_morph_targets(targets: &Image, source_vertices: &[u32]) -> Result<Image> {
+    let layers = read_morph_targets(targets).into_iter().map(|layer| {
+        source_vertices
+            .iter()
+            .map(move |&vertex| layer.get(vertex as usize).copied().unwrap_or_default())
+    });
+
+    let image = MorphTargetImage::new(
+        layers,
+        source_vertices.len(),
+        RenderAssetUsages::RENDER_WORLD,
+    )?;
+    Ok(image.0)
+}
+
+/// Checks that `mesh` is something the conversion can handle: a triangle list with
+/// `Float32x3` positions.
+pub fn check_convertible(mesh: &Mesh) -> Result<()> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return Err(anyhow!(
+            "unsupported topology {:?}",
+            mesh.primitive_topology()
+        ));
+    }
+    match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float32x3(_)) => Ok(()),
+        Some(_) => Err(anyhow!("positions aren't Float32x3")),
+        None => Err(anyhow!("mesh has no positions")),
+    }
+}
+
+/// Fails on meshes without `Float32x3` positions and normals, or without any lines.
+pub trait MeshToLineList {
+    fn mesh_to_line_list(&self) -> Result<LineList>;
+    fn mesh_to_feature_line_list(&self, crease_angle: f32) -> Result<LineList>;
+    fn mesh_to_quad_line_list(&self, coplanar_tolerance: f32) -> Result<LineList>;
+}
+
+impl MeshToLineList for Mesh {
+    fn mesh_to_line_list(&self) -> Result<LineList> {
+        mesh_to_line_list(self, EdgeMode::All, DEFAULT_WELD_TOLERANCE)
+    }
+
+    fn mesh_to_feature_line_list(&self, crease_angle: f32) -> Result<LineList> {
+        let mode = EdgeMode::FeatureEdges { crease_angle };
+        mesh_to_line_list(self, mode, DEFAULT_WELD_TOLERANCE)
+    }
+
+    fn mesh_to_quad_line_list(&self, coplanar_tolerance: f32) -> Result<LineList> {
+        let mode = EdgeMode::Quads { coplanar_tolerance };
+        mesh_to_line_list(self, mode, DEFAULT_WELD_TOLERANCE)
     }
 }
 
@@ -151,6 +414,19 @@ pub fn line_list_to_mesh(line_list: &LineList, mesh: &Mesh) -> Mesh {
 
     line_mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normal);
 
+    let distances: Vec<f32> = line_list
+        .lines
+        .iter()
+        .flat_map(|(start, end)| vec![0.0, line_length(start, end)])
+        .collect();
+    line_mesh.insert_attribute(ATTRIBUTE_LINE_DISTANCE, distances);
+    insert_reveal_order(&mut line_mesh, line_list, RevealOrder::Index);
+
+    line_mesh.insert_attribute(
+        ATTRIBUTE_LINE_EDGE_CLASS,
+        line_edge_classes(line_list, false),
+    );
+
     if let Some(VertexAttributeValues::Uint16x4(_)) = mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX) {
         let joint_indices: Vec<[u16; 4]> = line_list
             .lines
@@ -175,20 +451,248 @@ pub fn line_list_to_mesh(line_list: &LineList, mesh: &Mesh) -> Mesh {
         line_mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, joint_weights);
     }
 
+    // Edges without adjacent triangles fall back to the vertex normal for both faces, which
+    // never reads as a silhouette
+    let face_normals: Vec<[[f32; 3]; 2]> = line_list
+        .lines
+        .iter()
+        .flat_map(|(start, end)| {
+            vec![
+                start.face_normals.unwrap_or([start.normal; 2]),
+                end.face_normals.unwrap_or([end.normal; 2]),
+            ]
+        })
+        .collect();
+    line_mesh.insert_attribute(
+        ATTRIBUTE_FACE_NORMAL_A,
+        face_normals.iter().map(|[a, _]| *a).collect::<Vec<_>>(),
+    );
+    line_mesh.insert_attribute(
+        ATTRIBUTE_FACE_NORMAL_B,
+        face_normals.iter().map(|[_, b]| *b).collect::<Vec<_>>(),
+    );
+
+    line_mesh
+}
+
+/// The [`ATTRIBUTE_LINE_EDGE_CLASS`] of each vertex of the line mesh built from `line_list`,
+/// by [`line_list_to_thick_mesh`] when `thick`.
+fn line_edge_classes(line_list: &LineList, thick: bool) -> Vec<f32> {
+    let vertices_per_end = if thick { 2 } else { 1 };
+    line_list
+        .lines
+        .iter()
+        .flat_map(|(start, end)| {
+            [start.edge_class as u8 as f32; 2]
+                .into_iter()
+                .take(vertices_per_end)
+                .chain(
+                    [end.edge_class as u8 as f32; 2]
+                        .into_iter()
+                        .take(vertices_per_end),
+                )
+        })
+        .collect()
+}
+
+/// The edges of `mesh` that only one triangle has.
+pub fn boundary_edges(mesh: &Mesh, weld_tolerance: f32) -> Vec<[Vec3; 2]> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return vec![];
+    };
+    let weld = weld_positions(positions, weld_tolerance);
+    let adjacency = edge_adjacency(mesh, positions, &weld);
+    adjacency
+        .edge_faces
+        .iter()
+        .filter(|(_, faces)| faces.len() == 1)
+        .map(|((a, b), _)| {
+            let positions = &adjacency.vertex_positions;
+            [positions[*a as usize], positions[*b as usize]]
+        })
+        .collect()
+}
+
+/// Turns the boundary lines that run along one of `seams` into
+/// [`EdgeClass::MaterialBoundary`] lines, and updates the classes of `line_mesh`, built from
+/// `line_list`, to match. The seams are the [`boundary_edges`] of the other primitives of the
+/// mesh that have a different material.
+pub fn mark_material_boundaries(
+    line_list: &mut LineList,
+    line_mesh: &mut Mesh,
+    seams: &[[Vec3; 2]],
+    weld_tolerance: f32,
+) {
+    if seams.is_empty() {
+        return;
+    }
+
+    // Weld the seams and the lines together, so their ends are compared by ID
+    let positions: Vec<[f32; 3]> = seams
+        .iter()
+        .flat_map(|seam| seam.map(|position| position.to_array()))
+        .chain(
+            line_list
+                .lines
+                .iter()
+                .flat_map(|(start, end)| [start.position, end.position]),
+        )
+        .collect();
+    let weld = weld_positions(&positions, weld_tolerance);
+    let seam_keys: HashSet<EdgeKey> = (0..seams.len())
+        .map(|seam| weld.edge_key(seam * 2, seam * 2 + 1))
+        .collect();
+
+    let first_line = seams.len() * 2;
+    for (line, (start, end)) in line_list.lines.iter_mut().enumerate() {
+        let key = weld.edge_key(first_line + line * 2, first_line + line * 2 + 1);
+        if start.edge_class == EdgeClass::Boundary && seam_keys.contains(&key) {
+            start.edge_class = EdgeClass::MaterialBoundary;
+            end.edge_class = EdgeClass::MaterialBoundary;
+        }
+    }
+
+    let thick = line_mesh.primitive_topology() == PrimitiveTopology::TriangleList;
+    line_mesh.insert_attribute(
+        ATTRIBUTE_LINE_EDGE_CLASS,
+        line_edge_classes(line_list, thick),
+    );
+}
+
+fn line_length(start: &Vert, end: &Vert) -> f32 {
+    Vec3::from(start.position).distance(Vec3::from(end.position))
+}
+
+/// Expands every line into a quad of two triangles for lines wider than a pixel. Each
+/// corner carries the other end of its line and which side of it the corner is on, the
+/// vertex shader of [`crate::line_material::LineMaterial`] turns those into a camera-facing
+/// quad of the material's `width`.
+///
+/// Lines are independent quads, there are no miter joins where they meet. The material's
+/// round `line_cap` covers the notch at corners, butt caps leave it visible on wide lines.
+pub fn line_list_to_thick_mesh(line_list: &LineList, mesh: &Mesh) -> Mesh {
+    let mut line_mesh = Mesh::new(
+        bevy::render::render_resource::PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+
+    // (this end, other end, [side, 0.0 at the start or 1.0 at the end])
+    let corners: Vec<(&Vert, &Vert, [f32; 2])> = line_list
+        .lines
+        .iter()
+        .flat_map(|(start, end)| {
+            vec![
+                (start, end, [-1.0, 0.0]),
+                (start, end, [1.0, 0.0]),
+                (end, start, [-1.0, 1.0]),
+                (end, start, [1.0, 1.0]),
+            ]
+        })
+        .collect();
+
+    let positions: Vec<[f32; 3]> = corners.iter().map(|(this, _, _)| this.position).collect();
+    line_mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+
+    let other_ends: Vec<[f32; 3]> = corners.iter().map(|(_, other, _)| other.position).collect();
+    line_mesh.insert_attribute(ATTRIBUTE_LINE_OTHER_END, other_ends);
+
+    let sides: Vec<[f32; 2]> = corners.iter().map(|(_, _, side)| *side).collect();
+    line_mesh.insert_attribute(ATTRIBUTE_LINE_SIDE, sides);
+
+    let distances: Vec<f32> = corners
+        .iter()
+        .map(|(this, other, [_, end])| *end * line_length(this, other))
+        .collect();
+    line_mesh.insert_attribute(ATTRIBUTE_LINE_DISTANCE, distances);
+    insert_reveal_order(&mut line_mesh, line_list, RevealOrder::Index);
+
+    line_mesh.insert_attribute(
+        ATTRIBUTE_LINE_EDGE_CLASS,
+        line_edge_classes(line_list, true),
+    );
+
+    let colors: Vec<[f32; 4]> = corners
+        .iter()
+        .filter_map(|(this, _, _)| this.color)
+        .collect();
+    if colors.len() == corners.len() {
+        line_mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+
+    let normal: Vec<[f32; 3]> = corners.iter().map(|(this, _, _)| this.normal).collect();
+    line_mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normal);
+
+    // The other end is skinned with its own joints, otherwise the quads of a bent limb
+    // would point at where the other end would be without the bend
+    if let (Some(VertexAttributeValues::Uint16x4(_)), Some(VertexAttributeValues::Float32x4(_))) = (
+        mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX),
+        mesh.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT),
+    ) {
+        let joint_indices: Vec<[u16; 4]> = corners
+            .iter()
+            .filter_map(|(this, _, _)| this.joint_indices)
+            .collect();
+        let joint_weights: Vec<[f32; 4]> = corners
+            .iter()
+            .filter_map(|(this, _, _)| this.joint_weights)
+            .collect();
+        let other_joint_indices: Vec<[u16; 4]> = corners
+            .iter()
+            .filter_map(|(_, other, _)| other.joint_indices)
+            .collect();
+        let other_joint_weights: Vec<[f32; 4]> = corners
+            .iter()
+            .filter_map(|(_, other, _)| other.joint_weights)
+            .collect();
+        line_mesh.insert_attribute(
+            Mesh::ATTRIBUTE_JOINT_INDEX,
+            VertexAttributeValues::Uint16x4(joint_indices),
+        );
+        line_mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, joint_weights);
+        line_mesh.insert_attribute(
+            ATTRIBUTE_LINE_OTHER_JOINT_INDEX,
+            VertexAttributeValues::Uint16x4(other_joint_indices),
+        );
+        line_mesh.insert_attribute(ATTRIBUTE_LINE_OTHER_JOINT_WEIGHT, other_joint_weights);
+    }
+
+    let face_normals: Vec<[[f32; 3]; 2]> = corners
+        .iter()
+        .map(|(this, _, _)| this.face_normals.unwrap_or([this.normal; 2]))
+        .collect();
+    line_mesh.insert_attribute(
+        ATTRIBUTE_FACE_NORMAL_A,
+        face_normals.iter().map(|[a, _]| *a).collect::<Vec<_>>(),
+    );
+    line_mesh.insert_attribute(
+        ATTRIBUTE_FACE_NORMAL_B,
+        face_normals.iter().map(|[_, b]| *b).collect::<Vec<_>>(),
+    );
+
+    let indices: Vec<u32> = (0..line_list.lines.len() as u32)
+        .flat_map(|line| {
+            let first = line * 4;
+            [first, first + 1, first + 2, first, first + 2, first + 3]
+        })
+        .collect();
+    line_mesh.insert_indices(Indices::U32(indices));
+
     line_mesh
 }
 
-pub trait mesh_to_line_list_custom {
-    fn mesh_to_line_list_custom(&self, data: JsonLineList) -> LineList;
+pub trait MeshToLineListCustom {
+    fn mesh_to_line_list_custom(&self, data: &[[u32; 2]], weld_tolerance: f32) -> LineList;
 }
 
-impl mesh_to_line_list_custom for Mesh {
-    fn mesh_to_line_list_custom(&self, data: JsonLineList) -> LineList {
-        mesh_to_line_list_custom(self, data)
+impl MeshToLineListCustom for Mesh {
+    fn mesh_to_line_list_custom(&self, data: &[[u32; 2]], weld_tolerance: f32) -> LineList {
+        mesh_to_line_list_custom(self, data, weld_tolerance)
     }
 }
 
-fn mesh_to_line_list_custom(mesh: &Mesh, data: JsonLineList) -> LineList {
+fn mesh_to_line_list_custom(mesh: &Mesh, data: &[[u32; 2]], weld_tolerance: f32) -> LineList {
     let mut line_list = LineList::default();
     let mut edge_set = HashSet::new();
 
@@ -246,8 +750,13 @@ fn mesh_to_line_list_custom(mesh: &Mesh, data: JsonLineList) -> LineList {
             }
         }
 
+        let weld = weld_positions(positions, weld_tolerance);
+        let adjacency = edge_adjacency(mesh, positions, &weld);
+        let (crease_angle, coplanar_tolerance) = EdgeMode::All.classification_angles();
+        let edge_classes = adjacency.edge_classes(mesh, &weld, crease_angle, coplanar_tolerance);
+
         // Process the JSON line list
-        for &[index1, index2] in &data.line_list {
+        for &[index1, index2] in data {
             if let (Some(&v1), Some(&v2)) =
                 (index_to_vertex.get(&index1), index_to_vertex.get(&index2))
             {
@@ -255,12 +764,21 @@ fn mesh_to_line_list_custom(mesh: &Mesh, data: JsonLineList) -> LineList {
                 if edge_set.insert(edge) {
                     let i1 = v1 as usize;
                     let i2 = v2 as usize;
+                    let key = weld.edge_key(i1, i2);
+                    let face_normals = adjacency.face_normals_of(&key);
+                    let edge_class = edge_classes
+                        .get(&key)
+                        .copied()
+                        .unwrap_or(EdgeClass::Boundary);
                     let start = Vert {
                         position: positions[i1],
                         normal: normals[i1],
                         color: colors.map(|c| c[i1]),
                         joint_indices: joint_indices.map(|ji| ji[i1]),
                         joint_weights: joint_weights.map(|jw| jw[i1]),
+                        face_normals,
+                        source_vertex: Some(i1 as u32),
+                        edge_class,
                     };
                     let end = Vert {
                         position: positions[i2],
@@ -268,6 +786,9 @@ fn mesh_to_line_list_custom(mesh: &Mesh, data: JsonLineList) -> LineList {
                         color: colors.map(|c| c[i2]),
                         joint_indices: joint_indices.map(|ji| ji[i2]),
                         joint_weights: joint_weights.map(|jw| jw[i2]),
+                        face_normals,
+                        source_vertex: Some(i2 as u32),
+                        edge_class,
                     };
                     line_list.lines.push((start, end));
                 }
@@ -279,8 +800,7 @@ fn mesh_to_line_list_custom(mesh: &Mesh, data: JsonLineList) -> LineList {
     line_list
 }
 
-fn mesh_to_line_list(mesh: &Mesh) -> Result<LineList> {
-    
+fn mesh_to_line_list(mesh: &Mesh, mode: EdgeMode, weld_tolerance: f32) -> Result<LineList> {
     let mut line_list = LineList::default();
     let mut edge_set = HashSet::new();
 
@@ -322,16 +842,47 @@ fn mesh_to_line_list(mesh: &Mesh) -> Result<LineList> {
                 }
             });
 
+        // In the filtering modes only the edges in this set are kept. It is keyed by welded
+        // vertex so edges split along UV or normal seams are only emitted once.
+        let weld = weld_positions(positions, weld_tolerance);
+        let adjacency = edge_adjacency(mesh, positions, &weld);
+        let (crease_angle, coplanar_tolerance) = mode.classification_angles();
+        let edge_classes = adjacency.edge_classes(mesh, &weld, crease_angle, coplanar_tolerance);
+        let mut kept_edges = match mode {
+            EdgeMode::All => None,
+            EdgeMode::FeatureEdges { crease_angle } => {
+                Some(feature_edge_set(&adjacency, crease_angle))
+            }
+            EdgeMode::Quads { coplanar_tolerance } => {
+                Some(quad_edge_set(&adjacency, coplanar_tolerance))
+            }
+        };
+
         let mut process_triangle = |a: usize, b: usize, c: usize| {
             let mut add_edge = |v1: usize, v2: usize| {
-                let edge = if v1 < v2 { (v1, v2) } else { (v2, v1) };
-                if edge_set.insert(edge) {
+                let key = weld.edge_key(v1, v2);
+                let keep = match &mut kept_edges {
+                    Some(features) => features.remove(&key),
+                    None => {
+                        let edge = if v1 < v2 { (v1, v2) } else { (v2, v1) };
+                        edge_set.insert(edge)
+                    }
+                };
+                if keep {
+                    let face_normals = adjacency.face_normals_of(&key);
+                    let edge_class = edge_classes
+                        .get(&key)
+                        .copied()
+                        .unwrap_or(EdgeClass::Boundary);
                     let start = Vert {
                         position: positions[v1],
                         normal: normals[v1],
                         color: colors.map(|c| c[v1]),
                         joint_indices: joint_indices.map(|ji| ji[v1]),
                         joint_weights: joint_weights.map(|jw| jw[v1]),
+                        face_normals,
+                        source_vertex: Some(v1 as u32),
+                        edge_class,
                     };
                     let end = Vert {
                         position: positions[v2],
@@ -339,6 +890,9 @@ fn mesh_to_line_list(mesh: &Mesh) -> Result<LineList> {
                         color: colors.map(|c| c[v2]),
                         joint_indices: joint_indices.map(|ji| ji[v2]),
                         joint_weights: joint_weights.map(|jw| jw[v2]),
+                        face_normals,
+                        source_vertex: Some(v2 as u32),
+                        edge_class,
                     };
                     line_list.lines.push((start, end));
                 }
@@ -349,35 +903,7 @@ fn mesh_to_line_list(mesh: &Mesh) -> Result<LineList> {
             add_edge(c, a);
         };
 
-        if let Some(indices) = mesh.indices() {
-            match indices {
-                Indices::U16(idx) => {
-                    for triangle in idx.chunks(3) {
-                        process_triangle(
-                            triangle[0] as usize,
-                            triangle[1] as usize,
-                            triangle[2] as usize,
-                        );
-                    }
-                }
-                Indices::U32(idx) => {
-                    for triangle in idx.chunks(3) {
-                        process_triangle(
-                            triangle[0] as usize,
-                            triangle[1] as usize,
-                            triangle[2] as usize,
-                        );
-                    }
-                }
-            }
-        } else {
-            // Handle non-indexed geometry
-            for (i, triangle) in positions.chunks(3).enumerate() {
-                if triangle.len() == 3 {
-                    process_triangle(i * 3, i * 3 + 1, i * 3 + 2);
-                }
-            }
-        }
+        for_each_triangle(mesh, positions.len(), &mut process_triangle);
     } else {
         // warn!("mesh missing required data");
         return Err(anyhow!("mesh missing required data"));
@@ -390,18 +916,269 @@ fn mesh_to_line_list(mesh: &Mesh) -> Result<LineList> {
     Ok(line_list)
 }
 
+/// Calls `f` with the vertex indices of every triangle, for indexed and non-indexed meshes.
+fn for_each_triangle(mesh: &Mesh, vertex_count: usize, mut f: impl FnMut(usize, usize, usize)) {
+    if let Some(indices) = mesh.indices() {
+        match indices {
+            Indices::U16(idx) => {
+                for triangle in idx.chunks_exact(3) {
+                    f(
+                        triangle[0] as usize,
+                        triangle[1] as usize,
+                        triangle[2] as usize,
+                    );
+                }
+            }
+            Indices::U32(idx) => {
+                for triangle in idx.chunks_exact(3) {
+                    f(
+                        triangle[0] as usize,
+                        triangle[1] as usize,
+                        triangle[2] as usize,
+                    );
+                }
+            }
+        }
+    } else {
+        // Handle non-indexed geometry
+        for i in 0..vertex_count / 3 {
+            f(i * 3, i * 3 + 1, i * 3 + 2);
+        }
+    }
+}
+
+/// The welded vertex IDs of an edge, see [`Weld::edge_key`].
+type EdgeKey = (u32, u32);
+
+/// Triangles adjacent to each edge of a mesh, with edges welded by position.
+struct EdgeAdjacency {
+    face_normals: Vec<Vec3>,
+    /// The vertices of each triangle.
+    face_vertices: Vec<[usize; 3]>,
+    edge_faces: HashMap<EdgeKey, Vec<usize>>,
+    /// Position of each welded vertex.
+    vertex_positions: Vec<Vec3>,
+}
+
+fn edge_adjacency(mesh: &Mesh, positions: &[[f32; 3]], weld: &Weld) -> EdgeAdjacency {
+    let mut face_normals = Vec::new();
+    let mut face_vertices = Vec::new();
+    let mut edge_faces: HashMap<EdgeKey, Vec<usize>> = HashMap::new();
+
+    for_each_triangle(mesh, positions.len(), |a, b, c| {
+        let (pa, pb, pc) = (
+            Vec3::from_array(positions[a]),
+            Vec3::from_array(positions[b]),
+            Vec3::from_array(positions[c]),
+        );
+        let face = face_normals.len();
+        face_normals.push((pb - pa).cross(pc - pa).normalize_or_zero());
+        face_vertices.push([a, b, c]);
+
+        for (v1, v2) in [(a, b), (b, c), (c, a)] {
+            let key = weld.edge_key(v1, v2);
+            // Skip edges collapsed by welding
+            if key.0 != key.1 {
+                edge_faces.entry(key).or_default().push(face);
+            }
+        }
+    });
+
+    EdgeAdjacency {
+        face_normals,
+        face_vertices,
+        edge_faces,
+        vertex_positions: weld
+            .remap
+            .iter()
+            .map(|&vertex| Vec3::from(positions[vertex]))
+            .collect(),
+    }
+}
+
+impl EdgeAdjacency {
+    /// The normals of the two triangles sharing a manifold edge. Degenerate triangles have
+    /// no normal, so edges touching them are reported as non-manifold.
+    fn manifold_normals(&self, faces: &[usize]) -> Option<(Vec3, Vec3)> {
+        match faces {
+            [f1, f2] => {
+                let (n1, n2) = (self.face_normals[*f1], self.face_normals[*f2]);
+                (n1 != Vec3::ZERO && n2 != Vec3::ZERO).then_some((n1, n2))
+            }
+            _ => None,
+        }
+    }
+
+    /// The normals of the first two triangles sharing an edge. A boundary edge gets its
+    /// triangle's normal and the flipped normal, so it always counts as a silhouette.
+    fn face_normals_of(&self, key: &EdgeKey) -> Option<[[f32; 3]; 2]> {
+        match self.edge_faces.get(key)?.as_slice() {
+            [] => None,
+            [face] => {
+                let normal = self.face_normals[*face];
+                Some([normal.to_array(), (-normal).to_array()])
+            }
+            [f1, f2, ..] => Some([
+                self.face_normals[*f1].to_array(),
+                self.face_normals[*f2].to_array(),
+            ]),
+        }
+    }
+
+    /// The [`EdgeClass`] of every edge. Creases are edges whose adjacent face normals differ
+    /// by more than `crease_angle` radians, diagonals are found like in [`quad_edge_set`].
+    fn edge_classes(
+        &self,
+        mesh: &Mesh,
+        weld: &Weld,
+        crease_angle: f32,
+        coplanar_tolerance: f32,
+    ) -> HashMap<EdgeKey, EdgeClass> {
+        let cos_threshold = crease_angle.cos();
+        let diagonals = quad_diagonals(self, coplanar_tolerance);
+        let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            Some(VertexAttributeValues::Float32x2(uvs)) => Some(uvs),
+            _ => None,
+        };
+        let material_ids = match mesh.attribute(ATTRIBUTE_MATERIAL_ID) {
+            Some(VertexAttributeValues::Float32(ids)) => Some(ids),
+            _ => None,
+        };
+
+        self.edge_faces
+            .iter()
+            .map(|(key, faces)| {
+                let class = match faces.as_slice() {
+                    [f1, f2] => {
+                        let faces = [*f1, *f2];
+                        if material_ids
+                            .is_some_and(|ids| self.differs_across(weld, key, faces, ids))
+                        {
+                            EdgeClass::MaterialBoundary
+                        } else if self
+                            .manifold_normals(&faces)
+                            .is_some_and(|(n1, n2)| n1.dot(n2) < cos_threshold)
+                        {
+                            EdgeClass::Crease
+                        } else if uvs.is_some_and(|uvs| self.differs_across(weld, key, faces, uvs))
+                        {
+                            EdgeClass::UvSeam
+                        } else if diagonals.contains(key) {
+                            EdgeClass::Diagonal
+                        } else {
+                            EdgeClass::Interior
+                        }
+                    }
+                    _ => EdgeClass::Boundary,
+                };
+                (*key, class)
+            })
+            .collect()
+    }
+
+    /// Whether two triangles sharing an edge have different `values` at either end of it.
+    /// Welding lets each triangle have its own vertex there.
+    fn differs_across<T: PartialEq>(
+        &self,
+        weld: &Weld,
+        key: &EdgeKey,
+        [f1, f2]: [usize; 2],
+        values: &[T],
+    ) -> bool {
+        [key.0, key.1].into_iter().any(|id| {
+            let corner = |face: usize| {
+                self.face_vertices[face]
+                    .into_iter()
+                    .find(|&vertex| weld.id(vertex) == id)
+            };
+            match (corner(f1), corner(f2)) {
+                (Some(v1), Some(v2)) => values[v1] != values[v2],
+                _ => false,
+            }
+        })
+    }
+}
+
+/// Collects the edges that are on a boundary, shared by more than two triangles, or whose
+/// adjacent face normals differ by more than `crease_angle` radians.
+fn feature_edge_set(adjacency: &EdgeAdjacency, crease_angle: f32) -> HashSet<EdgeKey> {
+    let cos_threshold = crease_angle.cos();
+
+    adjacency
+        .edge_faces
+        .iter()
+        .filter(|(_, faces)| match faces.as_slice() {
+            [_, _] => adjacency
+                .manifold_normals(faces)
+                .is_some_and(|(n1, n2)| n1.dot(n2) < cos_threshold),
+            _ => true,
+        })
+        .map(|(key, _)| *key)
+        .collect()
+}
+
+/// Collects every edge except the diagonals of recovered quads.
+fn quad_edge_set(adjacency: &EdgeAdjacency, coplanar_tolerance: f32) -> HashSet<EdgeKey> {
+    let diagonals = quad_diagonals(adjacency, coplanar_tolerance);
+
+    adjacency
+        .edge_faces
+        .keys()
+        .filter(|key| !diagonals.contains(*key))
+        .copied()
+        .collect()
+}
+
+/// Collects the diagonals of quads recovered from pairs of near coplanar triangles.
+///
+/// Triangles are paired greedily across their longest shared edges first, since that is
+/// the diagonal of a quad, and each triangle ends up in at most one quad.
+fn quad_diagonals(adjacency: &EdgeAdjacency, coplanar_tolerance: f32) -> HashSet<EdgeKey> {
+    let cos_tolerance = coplanar_tolerance.cos();
+
+    let mut candidates: Vec<(EdgeKey, usize, usize)> = adjacency
+        .edge_faces
+        .iter()
+        .filter_map(|(key, faces)| {
+            let (n1, n2) = adjacency.manifold_normals(faces)?;
+            (n1.dot(n2) >= cos_tolerance).then_some((*key, faces[0], faces[1]))
+        })
+        .collect();
+
+    let key_length = |(k1, k2): &EdgeKey| {
+        let positions = &adjacency.vertex_positions;
+        positions[*k1 as usize].distance_squared(positions[*k2 as usize])
+    };
+    candidates.sort_by(|a, b| {
+        key_length(&b.0)
+            .total_cmp(&key_length(&a.0))
+            .then(a.0.cmp(&b.0))
+    });
+
+    let mut paired = vec![false; adjacency.face_normals.len()];
+    let mut diagonals = HashSet::new();
+    for (key, f1, f2) in candidates {
+        if !paired[f1] && !paired[f2] {
+            paired[f1] = true;
+            paired[f2] = true;
+            diagonals.insert(key);
+        }
+    }
+
+    diagonals
+}
 
 pub trait SmoothNormalsNonIndexed {
-    fn smooth_normals_non_indexed(&mut self);
+    fn smooth_normals_non_indexed(&mut self, weld_tolerance: f32);
 }
 
 impl SmoothNormalsNonIndexed for Mesh {
-    fn smooth_normals_non_indexed(&mut self) {
-        smooth_normals_non_indexed(self);
+    fn smooth_normals_non_indexed(&mut self, weld_tolerance: f32) {
+        smooth_normals_non_indexed(self, weld_tolerance);
     }
 }
 
-fn smooth_normals_non_indexed(mesh: &mut Mesh) {
+fn smooth_normals_non_indexed(mesh: &mut Mesh, weld_tolerance: f32) {
     if let (
         Some(VertexAttributeValues::Float32x3(positions)),
         Some(VertexAttributeValues::Float32x3(normals)),
@@ -409,36 +1186,305 @@ fn smooth_normals_non_indexed(mesh: &mut Mesh) {
         mesh.attribute(Mesh::ATTRIBUTE_POSITION),
         mesh.attribute(Mesh::ATTRIBUTE_NORMAL),
     ) {
-        let mut normal_map: HashMap<(i32, i32, i32), Vec3> = HashMap::new();
-
-        // Function to convert float position to integer key
-        let to_key = |pos: &[f32; 3]| {
-            (
-                (pos[0] * 1000.0).round() as i32,
-                (pos[1] * 1000.0).round() as i32,
-                (pos[2] * 1000.0).round() as i32,
-            )
-        };
+        let weld = weld_positions(positions, weld_tolerance);
+        let mut welded_normals = vec![Vec3::ZERO; weld.len()];
 
-        // Sum up normals for each unique position
-        for (position, normal) in positions.iter().zip(normals.iter()) {
-            let key = to_key(position);
-            let entry = normal_map.entry(key).or_insert(Vec3::ZERO);
-            *entry += Vec3::from_array(*normal);
+        // Sum up normals for each welded vertex
+        for (vertex, normal) in normals.iter().enumerate() {
+            welded_normals[weld.id(vertex) as usize] += Vec3::from_array(*normal);
         }
 
         // Normalize the summed normals
-        for normal in normal_map.values_mut() {
+        for normal in &mut welded_normals {
             *normal = normal.normalize();
         }
 
         // Create new normalized normals
-        let new_normals: Vec<[f32; 3]> = positions
-            .iter()
-            .map(|pos| normal_map[&to_key(pos)].to_array())
+        let new_normals: Vec<[f32; 3]> = (0..positions.len())
+            .map(|vertex| welded_normals[weld.id(vertex) as usize].to_array())
             .collect();
 
         // Update the mesh with new normals
         mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, new_normals);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use bevy::{
+        prelude::{Cuboid, Meshable, Plane3d},
+        render::render_resource::PrimitiveTopology,
+    };
+
+    use super::*;
+
+    /// Non-indexed triangles facing up.
+    fn triangles(positions: Vec<[f32; 3]>) -> Mesh {
+        let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    }
+
+    /// The triangles of a unit quad on the XZ plane at `x`, `z`, split along its diagonal
+    /// from the origin corner.
+    fn quad_positions(x: f32, z: f32) -> Vec<[f32; 3]> {
+        vec![
+            [x, 0.0, z],
+            [x, 0.0, z + 1.0],
+            [x + 1.0, 0.0, z + 1.0],
+            [x, 0.0, z],
+            [x + 1.0, 0.0, z + 1.0],
+            [x + 1.0, 0.0, z],
+        ]
+    }
+
+    fn quad(x: f32) -> Mesh {
+        triangles(quad_positions(x, 0.0))
+    }
+
+    /// Whether a line on the XZ plane runs across a grid cell rather than along its sides.
+    fn crosses_cell((start, end): &(Vert, Vert)) -> bool {
+        start.position[0] != end.position[0] && start.position[2] != end.position[2]
+    }
+
+    fn class_counts(line_list: &LineList) -> HashMap<EdgeClass, usize> {
+        let mut counts = HashMap::new();
+        for (start, end) in &line_list.lines {
+            assert_eq!(start.edge_class, end.edge_class);
+            *counts.entry(start.edge_class).or_default() += 1;
+        }
+        counts
+    }
+
+    fn line_list(mesh: &Mesh) -> LineList {
+        mesh_to_line_list(mesh, EdgeMode::All, DEFAULT_WELD_TOLERANCE).unwrap()
+    }
+
+    fn line(start: [f32; 3], end: [f32; 3]) -> (Vert, Vert) {
+        let vert = |position| Vert {
+            position,
+            ..Default::default()
+        };
+        (vert(start), vert(end))
+    }
+
+    /// A chain along X from the origin, its lines out of order and not all pointing the same
+    /// way, and a line away from it.
+    fn chain() -> LineList {
+        LineList {
+            lines: vec![
+                line([2.0, 0.0, 0.0], [3.0, 0.0, 0.0]),
+                line([1.0, 0.0, 0.0], [0.0, 0.0, 0.0]),
+                line([1.0, 0.0, 0.0], [2.0, 0.0, 0.0]),
+                line([10.0, 0.0, 0.0], [11.0, 0.0, 0.0]),
+            ],
+        }
+    }
+
+    #[test]
+    fn cube_edges_are_creases_and_diagonals() {
+        // Every face has its own vertices, so each cube edge is emitted by both faces
+        let counts = class_counts(&line_list(&Mesh::from(Cuboid::default())));
+        assert_eq!(
+            counts,
+            HashMap::from_iter([(EdgeClass::Crease, 24), (EdgeClass::Diagonal, 6)])
+        );
+    }
+
+    #[test]
+    fn feature_edges_of_a_cube_are_its_creases() {
+        let cube = Mesh::from(Cuboid::default());
+        let mode = EdgeMode::FeatureEdges {
+            crease_angle: DEFAULT_CREASE_ANGLE,
+        };
+        // Unlike every triangle edge, feature edges are only emitted once per welded edge
+        let line_list = mesh_to_line_list(&cube, mode, DEFAULT_WELD_TOLERANCE).unwrap();
+        assert_eq!(
+            class_counts(&line_list),
+            HashMap::from_iter([(EdgeClass::Crease, 12)])
+        );
+
+        // The faces of a cube meet at right angles, so none bends more than this
+        let mode = EdgeMode::FeatureEdges {
+            crease_angle: FRAC_PI_2 + 0.1,
+        };
+        assert!(mesh_to_line_list(&cube, mode, DEFAULT_WELD_TOLERANCE).is_err());
+    }
+
+    #[test]
+    fn feature_edges_of_a_flat_plane_are_its_boundary() {
+        let plane = Mesh::from(Plane3d::default().mesh().subdivisions(1));
+        let mode = EdgeMode::FeatureEdges {
+            crease_angle: DEFAULT_CREASE_ANGLE,
+        };
+        let line_list = mesh_to_line_list(&plane, mode, DEFAULT_WELD_TOLERANCE).unwrap();
+        assert_eq!(
+            class_counts(&line_list),
+            HashMap::from_iter([(EdgeClass::Boundary, 8)])
+        );
+    }
+
+    #[test]
+    fn quads_drop_the_diagonals_of_a_grid() {
+        let grid = triangles(
+            [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]
+                .into_iter()
+                .flat_map(|(x, z)| quad_positions(x, z))
+                .collect(),
+        );
+        let all = line_list(&grid);
+        let mode = EdgeMode::Quads {
+            coplanar_tolerance: DEFAULT_COPLANAR_TOLERANCE,
+        };
+        let quads = mesh_to_line_list(&grid, mode, DEFAULT_WELD_TOLERANCE).unwrap();
+
+        // Both triangles emit the diagonal they share
+        let diagonals = all.lines.iter().filter(|line| crosses_cell(line));
+        assert_eq!(diagonals.count(), 8);
+        // While the quad edges are emitted once per welded edge
+        assert!(!quads.lines.iter().any(crosses_cell));
+        assert_eq!(quads.lines.len(), 12);
+    }
+
+    #[test]
+    fn quads_keep_the_diagonal_of_a_folded_quad() {
+        let folded = triangles(vec![
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 1.0, 0.0],
+        ]);
+        let mode = EdgeMode::Quads {
+            coplanar_tolerance: DEFAULT_COPLANAR_TOLERANCE,
+        };
+        let quads = mesh_to_line_list(&folded, mode, DEFAULT_WELD_TOLERANCE).unwrap();
+        let diagonals = quads.lines.iter().filter(|line| crosses_cell(line));
+        assert_eq!(diagonals.count(), 1);
+    }
+
+    #[test]
+    fn graph_distances_hop_along_the_chain() {
+        let seed = Vec3::new(-0.5, 0.0, 0.0);
+        // The unconnected line comes one hop after the far end of the chain
+        assert_eq!(
+            graph_distances(&chain(), seed),
+            [(2.0, 3.0), (1.0, 0.0), (1.0, 2.0), (4.0, 4.0)]
+        );
+        assert_eq!(
+            chain().reveal_order(RevealOrder::GraphDistance { seed }),
+            [(0.5, false), (0.0, true), (0.25, false), (1.0, false)]
+        );
+    }
+
+    #[test]
+    fn reveal_order_is_written_to_every_vertex_of_a_line() {
+        let chain = chain();
+        let mut line_mesh = line_list_to_thick_mesh(&chain, &triangles(vec![]));
+        let mut reveal = |order| {
+            insert_reveal_order(&mut line_mesh, &chain, order);
+            let Some(VertexAttributeValues::Float32x3(reveal)) =
+                line_mesh.attribute(ATTRIBUTE_LINE_REVEAL)
+            else {
+                panic!("line mesh has no reveal order");
+            };
+            // Each line of a thick mesh is a quad, its corners all get the same values
+            assert_eq!(reveal.len(), chain.lines.len() * 4);
+            let lines: Vec<[f32; 3]> = reveal.chunks_exact(4).map(|quad| quad[0]).collect();
+            assert!(reveal.chunks_exact(4).all(|quad| quad == [quad[0]; 4]));
+            lines
+        };
+
+        // Every line is at the same height, so they all start at once
+        assert_eq!(reveal(RevealOrder::Height), [[0.0, 1.0, 0.0]; 4]);
+        assert_eq!(
+            reveal(RevealOrder::DistanceFrom(Vec3::ZERO)),
+            [
+                [0.2, 1.0, 0.0],
+                [0.0, 1.0, 1.0],
+                [0.1, 1.0, 0.0],
+                [1.0, 1.0, 0.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn plane_edges_are_boundaries_interiors_and_diagonals() {
+        let plane = Mesh::from(Plane3d::default().mesh().subdivisions(1));
+        let counts = class_counts(&line_list(&plane));
+        assert_eq!(
+            counts,
+            HashMap::from_iter([
+                (EdgeClass::Boundary, 8),
+                (EdgeClass::Interior, 4),
+                (EdgeClass::Diagonal, 4),
+            ])
+        );
+    }
+
+    #[test]
+    fn edges_with_split_uvs_are_uv_seams() {
+        let mut mesh = quad(0.0);
+        let uvs: Vec<[f32; 2]> = vec![
+            [0.0, 0.0],
+            [0.0, 1.0],
+            [1.0, 1.0],
+            [0.5, 0.0],
+            [1.0, 0.5],
+            [1.0, 0.0],
+        ];
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+
+        let counts = class_counts(&line_list(&mesh));
+        assert_eq!(
+            counts,
+            HashMap::from_iter([(EdgeClass::Boundary, 4), (EdgeClass::UvSeam, 2)])
+        );
+    }
+
+    #[test]
+    fn edges_between_material_ids_are_material_boundaries() {
+        let mut mesh = quad(0.0);
+        mesh.insert_attribute(ATTRIBUTE_MATERIAL_ID, vec![0.0f32, 0.0, 0.0, 1.0, 1.0, 1.0]);
+
+        let counts = class_counts(&line_list(&mesh));
+        assert_eq!(counts[&EdgeClass::MaterialBoundary], 2);
+    }
+
+    #[test]
+    fn boundaries_shared_with_other_primitives_are_material_boundaries() {
+        let (left, right) = (quad(0.0), quad(1.0));
+        let mut line_list = line_list(&left);
+        let mut line_mesh = line_list_to_mesh(&line_list, &left);
+        let seams = boundary_edges(&right, DEFAULT_WELD_TOLERANCE);
+        assert_eq!(seams.len(), 4);
+
+        mark_material_boundaries(
+            &mut line_list,
+            &mut line_mesh,
+            &seams,
+            DEFAULT_WELD_TOLERANCE,
+        );
+        let counts = class_counts(&line_list);
+        assert_eq!(counts[&EdgeClass::MaterialBoundary], 1);
+        assert_eq!(counts[&EdgeClass::Boundary], 3);
+
+        let Some(VertexAttributeValues::Float32(classes)) =
+            line_mesh.attribute(ATTRIBUTE_LINE_EDGE_CLASS)
+        else {
+            panic!("line mesh has no edge classes");
+        };
+        let material_boundary = EdgeClass::MaterialBoundary as u8 as f32;
+        assert_eq!(
+            classes.iter().filter(|&&c| c == material_boundary).count(),
+            2
+        );
+    }
+}
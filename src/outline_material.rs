@@ -27,6 +27,16 @@ pub struct OutlineMaterial {
     pub z_translate: f32,
     #[uniform(0)]
     pub use_vertex_color: i32,
+    /// Depth cueing, see [`crate::line_material::LineMaterial::fog_near`].
+    #[uniform(0)]
+    pub fog_near: f32,
+    #[uniform(0)]
+    pub fog_far: f32,
+    #[uniform(0)]
+    pub fog_color: Vec4,
+    /// Multiplies the color, values above 1.0 make the outline bloom.
+    #[uniform(0)]
+    pub intensity: f32,
 }
 
 
@@ -67,6 +77,10 @@ impl Default for OutlineMaterial {
             outline_width: 0.0,
             z_translate: 0.1,
             use_vertex_color: 1,
+            fog_near: 0.0,
+            fog_far: 0.0,
+            fog_color: Vec4::new(0.0, 0.0, 0.0, 1.0),
+            intensity: 1.0,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,235 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    fill_material::FillMaterial,
+    line_material::{LineMaterial, DEPTH_TEST_ALWAYS, DEPTH_TEST_VISIBLE},
+    outline_material::OutlineMaterial,
+    WireframeLayerOf,
+};
+
+/// How the meshes of a converted scene are drawn, switchable at any time. Goes next to the
+/// [`crate::WireframeSettings`], or on a single mesh entity below them to override their
+/// style for that mesh.
+///
+/// Only the layers enabled in [`crate::WireframeSettings::layers`] were generated, the
+/// others stay off in every style.
+///
+/// Removing the component falls back to the scene's style, or to [`RenderStyle::Original`]
+/// when the scene has none.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RenderStyle {
+    /// The mesh and material the scene was loaded with.
+    Original,
+    /// The lines alone.
+    Wireframe,
+    /// Every generated layer, the look right after conversion.
+    #[default]
+    FillPlusWire,
+    /// The fill and its outline, without lines.
+    OutlineOnly,
+    /// The fill and the lines in front of it. The dimmed lines of
+    /// [`crate::HIDDEN_LINES_DIM`] are left out and [`crate::HIDDEN_LINES_XRAY`] lines are
+    /// depth tested.
+    HiddenLine,
+}
+
+impl RenderStyle {
+    fn fill(self) -> bool {
+        matches!(
+            self,
+            Self::FillPlusWire | Self::OutlineOnly | Self::HiddenLine
+        )
+    }
+
+    fn outline(self) -> bool {
+        matches!(self, Self::FillPlusWire | Self::OutlineOnly)
+    }
+
+    fn lines(self) -> bool {
+        matches!(
+            self,
+            Self::Wireframe | Self::FillPlusWire | Self::HiddenLine
+        )
+    }
+
+    fn hidden_lines(self) -> bool {
+        self == Self::FillPlusWire
+    }
+}
+
+/// What a converted mesh entity was loaded with and what it was converted to, for swapping
+/// between them in [`apply_render_styles`].
+#[derive(Component, Clone)]
+pub(crate) struct RenderStyleAssets {
+    pub(crate) scene: Entity,
+    pub(crate) original_mesh: Handle<Mesh>,
+    pub(crate) original_material: Option<Handle<StandardMaterial>>,
+    pub(crate) flat_mesh: Handle<Mesh>,
+    pub(crate) fill: Option<Handle<FillMaterial>>,
+    pub(crate) outline: Option<Handle<OutlineMaterial>>,
+}
+
+/// Marks the line entity drawing the dimmed hidden lines of [`crate::HIDDEN_LINES_DIM`].
+#[derive(Component)]
+pub(crate) struct HiddenLinesLayer;
+
+/// Marks the line entity of a [`crate::HIDDEN_LINES_XRAY`] mesh, whose material draws
+/// through the model outside of [`RenderStyle::HiddenLine`].
+#[derive(Component)]
+pub(crate) struct XRayLinesLayer;
+
+/// Marks a mesh entity reverted to [`RenderStyle::Original`] by the removal of its style, so
+/// it stays that way when converted again, until a new [`RenderStyle`] is inserted.
+#[derive(Component)]
+pub(crate) struct StyleRemoved;
+
+/// Swaps the materials of newly (re)converted meshes and of meshes whose [`RenderStyle`]
+/// changed or was removed, and shows or hides their line entities.
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_render_styles(
+    mut commands: Commands,
+    styles: Query<Ref<RenderStyle>>,
+    mut removed_styles: RemovedComponents<RenderStyle>,
+    meshes: Query<(Entity, Ref<RenderStyleAssets>, Has<StyleRemoved>)>,
+    mut lines: Query<(
+        &WireframeLayerOf,
+        &mut Visibility,
+        &Handle<LineMaterial>,
+        Has<HiddenLinesLayer>,
+        Has<XRayLinesLayer>,
+    )>,
+    mut line_materials: ResMut<Assets<LineMaterial>>,
+) {
+    let removed: HashSet<Entity> = removed_styles.read().collect();
+    let mut applied = HashMap::new();
+
+    for (entity, assets, was_removed) in &meshes {
+        // The mesh's own style wins over the scene's
+        let style = styles
+            .get(entity)
+            .or_else(|_| styles.get(assets.scene))
+            .ok();
+        let style_removed = removed.contains(&entity) || removed.contains(&assets.scene);
+        // The assets change when the mesh is converted again
+        if !assets.is_changed() && !style.as_ref().is_some_and(Ref::is_changed) && !style_removed {
+            continue;
+        }
+
+        let mut entity_commands = commands.entity(entity);
+        let style = match style {
+            Some(style) => {
+                if was_removed {
+                    entity_commands.remove::<StyleRemoved>();
+                }
+                *style
+            }
+            // Taking the last style away undoes the conversion
+            None if style_removed || was_removed => {
+                entity_commands.insert(StyleRemoved);
+                RenderStyle::Original
+            }
+            None => RenderStyle::default(),
+        };
+        if style == RenderStyle::Original {
+            entity_commands
+                .remove::<(Handle<FillMaterial>, Handle<OutlineMaterial>)>()
+                .insert(assets.original_mesh.clone());
+            if let Some(material) = assets.original_material.clone() {
+                entity_commands.insert(material);
+            }
+        } else {
+            entity_commands
+                .remove::<Handle<StandardMaterial>>()
+                .insert(assets.flat_mesh.clone());
+            match assets.fill.clone().filter(|_| style.fill()) {
+                Some(fill) => entity_commands.insert(fill),
+                None => entity_commands.remove::<Handle<FillMaterial>>(),
+            };
+            match assets.outline.clone().filter(|_| style.outline()) {
+                Some(outline) => entity_commands.insert(outline),
+                None => entity_commands.remove::<Handle<OutlineMaterial>>(),
+            };
+        }
+        applied.insert(entity, style);
+    }
+
+    for (layer_of, mut visibility, material, hidden_lines, xray) in &mut lines {
+        let Some(&style) = applied.get(&layer_of.0) else {
+            continue;
+        };
+        let shown = style.lines() && (!hidden_lines || style.hidden_lines());
+        *visibility = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        if xray {
+            // Every line entity has its own material, so it can be changed in place
+            let depth_test = if style == RenderStyle::HiddenLine {
+                DEPTH_TEST_VISIBLE
+            } else {
+                DEPTH_TEST_ALWAYS
+            };
+            if line_materials
+                .get(material)
+                .is_some_and(|material| material.depth_test != depth_test)
+            {
+                if let Some(material) = line_materials.get_mut(material) {
+                    material.depth_test = depth_test;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removed_styles_stay_removed_across_conversions() {
+        let mut world = World::new();
+        world.init_resource::<Assets<LineMaterial>>();
+        let system = world.register_system(apply_render_styles);
+
+        let scene = world.spawn(RenderStyle::FillPlusWire).id();
+        let mesh = world
+            .spawn(RenderStyleAssets {
+                scene,
+                original_mesh: Handle::default(),
+                original_material: Some(Handle::default()),
+                flat_mesh: Handle::default(),
+                fill: Some(Handle::default()),
+                outline: None,
+            })
+            .id();
+        let original = |world: &World| {
+            let mesh = world.entity(mesh);
+            mesh.contains::<Handle<StandardMaterial>>() && !mesh.contains::<Handle<FillMaterial>>()
+        };
+
+        world.run_system(system).unwrap();
+        assert!(!original(&world));
+
+        world.entity_mut(scene).remove::<RenderStyle>();
+        world.run_system(system).unwrap();
+        assert!(original(&world));
+
+        // Converted again, as when its edge list is hot-reloaded
+        world
+            .get_mut::<RenderStyleAssets>(mesh)
+            .unwrap()
+            .set_changed();
+        world.run_system(system).unwrap();
+        assert!(original(&world));
+
+        world.entity_mut(mesh).insert(RenderStyle::OutlineOnly);
+        world.run_system(system).unwrap();
+        assert!(!original(&world));
+        assert!(!world.entity(mesh).contains::<StyleRemoved>());
+    }
+}
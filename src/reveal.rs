@@ -0,0 +1,139 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{line_material::LineMaterial, wireframe_plugin::WireframeConversion, WireframeScene};
+
+/// Draws the lines of a wireframe scene on progressively, each line growing from one end
+/// to the other in the order set by [`crate::WireframeSettings::reveal_order`]. Goes next to
+/// the `WireframeSettings`.
+#[derive(Component, Clone, Debug)]
+pub struct WireframeReveal {
+    /// 0.0 shows no lines, 1.0 shows all of them.
+    pub progress: f32,
+    /// How much of the progress each line takes to grow. Larger values have more lines
+    /// growing at once.
+    pub spread: f32,
+    /// Seconds for `progress` to run from 0.0 to 1.0, counted from when the scene's lines
+    /// are all converted. With `None`, `progress` is left to be set by hand.
+    pub duration: Option<f32>,
+}
+
+impl Default for WireframeReveal {
+    fn default() -> Self {
+        Self::timed(3.0)
+    }
+}
+
+impl WireframeReveal {
+    /// A reveal that plays over `seconds`.
+    pub fn timed(seconds: f32) -> Self {
+        Self {
+            progress: 0.0,
+            spread: 0.1,
+            duration: Some(seconds),
+        }
+    }
+
+    /// A reveal held at `progress` until changed.
+    pub fn manual(progress: f32) -> Self {
+        Self {
+            progress,
+            spread: 0.1,
+            duration: None,
+        }
+    }
+}
+
+/// Plays timed reveals. A scene still loading or converting has nothing to draw on yet, so
+/// its reveal waits until it has lines and none of its meshes are pending.
+pub(crate) fn advance_reveals(
+    time: Res<Time>,
+    mut reveals: Query<(Entity, &mut WireframeReveal)>,
+    lines: Query<&WireframeScene, With<Handle<LineMaterial>>>,
+    conversions: Query<&WireframeConversion>,
+) {
+    let with_lines: HashSet<Entity> = lines.iter().map(|scene| scene.0).collect();
+    let converting: HashSet<Entity> = conversions.iter().map(|c| c.scene).collect();
+
+    for (scene, mut reveal) in &mut reveals {
+        let Some(duration) = reveal.duration else {
+            continue;
+        };
+        if reveal.progress >= 1.0 || !with_lines.contains(&scene) || converting.contains(&scene) {
+            continue;
+        }
+        reveal.progress = if duration > 0.0 {
+            (reveal.progress + time.delta_seconds() / duration).min(1.0)
+        } else {
+            1.0
+        };
+    }
+}
+
+pub(crate) fn apply_reveals(
+    reveals: Query<Ref<WireframeReveal>>,
+    lines: Query<(Ref<WireframeScene>, &Handle<LineMaterial>)>,
+    mut line_materials: ResMut<Assets<LineMaterial>>,
+) {
+    for (scene, material_handle) in &lines {
+        let Ok(reveal) = reveals.get(scene.0) else {
+            continue;
+        };
+        if !reveal.is_changed() && !scene.is_added() {
+            continue;
+        }
+        if let Some(material) = line_materials.get_mut(material_handle) {
+            material.reveal_progress = reveal.progress;
+            material.reveal_spread = reveal.spread;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{mesh_cache::WireframeMeshes, WireframeSettings};
+
+    fn advance(world: &mut World, seconds: f32) {
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs_f32(seconds));
+        world.insert_resource(time);
+        world.run_system_once(advance_reveals);
+    }
+
+    #[test]
+    fn reveals_wait_for_the_conversion() {
+        let mut world = World::new();
+        let scene = world.spawn(WireframeReveal::timed(1.0)).id();
+        let progress = |world: &World| world.get::<WireframeReveal>(scene).unwrap().progress;
+
+        // Still loading, nothing converted yet
+        advance(&mut world, 0.5);
+        assert_eq!(progress(&world), 0.0);
+
+        // Some lines are in, but a mesh is still converting
+        world.spawn((WireframeScene(scene), Handle::<LineMaterial>::default()));
+        let pending = world
+            .spawn(WireframeConversion {
+                meshes: WireframeMeshes {
+                    flat_mesh: Handle::default(),
+                    line_mesh: None,
+                    source_mesh: None,
+                    morphed: false,
+                },
+                scene,
+                settings: WireframeSettings::default(),
+                edge_list_source: None,
+            })
+            .id();
+        advance(&mut world, 0.5);
+        assert_eq!(progress(&world), 0.0);
+
+        world.entity_mut(pending).remove::<WireframeConversion>();
+        advance(&mut world, 0.5);
+        assert_eq!(progress(&world), 0.5);
+    }
+}
@@ -0,0 +1,487 @@
+use bevy::{
+    color::{Color, ColorToComponents},
+    ecs::system::SystemParam,
+    prelude::*,
+    render::{mesh::VertexAttributeValues, render_resource::TextureFormat},
+    utils::HashMap,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    render_style::RenderStyleAssets,
+    weld::{weld_positions, DEFAULT_WELD_TOLERANCE},
+};
+
+/// How the vertex colors used by the line and outline layers are chosen.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VertexColorStrategy {
+    /// A random color per vertex, shared by vertices at the same position.
+    Random {
+        seed: u64,
+    },
+    /// Blends from `from` to `to` along `axis`, over the extent of the mesh.
+    AxisGradient {
+        axis: Vec3,
+        from: Vec4,
+        to: Vec4,
+    },
+    /// Blends from `low` at the bottom of the mesh to `high` at the top.
+    Height {
+        low: Vec4,
+        high: Vec4,
+    },
+    /// The vertex normal mapped to RGB, like a normal map.
+    Normal,
+    /// One random color per mesh.
+    PerMesh {
+        seed: u64,
+    },
+    /// One random color per connected part of a mesh.
+    PerPart {
+        seed: u64,
+    },
+    /// Samples the base color texture of the mesh's original material at the vertex UVs,
+    /// times its base color.
+    Texture,
+    /// Leaves the mesh's own vertex colors alone, white for meshes without any.
+    KeepOriginal,
+    Constant(Vec4),
+}
+
+impl Default for VertexColorStrategy {
+    fn default() -> Self {
+        Self::Random { seed: 0 }
+    }
+}
+
+/// What a [`VertexColorStrategy`] may need to know about the mesh beyond its attributes.
+#[derive(Clone, Copy)]
+pub struct VertexColorSource<'a> {
+    /// Identifies the mesh for [`VertexColorStrategy::PerMesh`].
+    pub mesh_key: u64,
+    /// The original material's base color.
+    pub base_color: Option<Vec4>,
+    /// The original material's base color texture.
+    pub base_color_texture: Option<&'a Image>,
+    /// Vertices closer than this share a color in [`VertexColorStrategy::Random`] and
+    /// connect parts in [`VertexColorStrategy::PerPart`].
+    pub weld_tolerance: f32,
+}
+
+impl Default for VertexColorSource<'_> {
+    fn default() -> Self {
+        Self {
+            mesh_key: 0,
+            base_color: None,
+            base_color_texture: None,
+            weld_tolerance: DEFAULT_WELD_TOLERANCE,
+        }
+    }
+}
+
+/// The original materials of glTF meshes, for [`VertexColorStrategy::Texture`].
+#[derive(SystemParam)]
+pub(crate) struct OriginalMaterials<'w, 's> {
+    handles: Query<
+        'w,
+        's,
+        (
+            Option<&'static Handle<StandardMaterial>>,
+            Option<&'static RenderStyleAssets>,
+        ),
+    >,
+    materials: Res<'w, Assets<StandardMaterial>>,
+    images: Res<'w, Assets<Image>>,
+}
+
+impl OriginalMaterials<'_, '_> {
+    /// Converted meshes only keep their original material in their [`RenderStyleAssets`].
+    fn handle(&self, entity: Entity) -> Option<&Handle<StandardMaterial>> {
+        match self.handles.get(entity).ok()? {
+            (_, Some(assets)) => assets.original_material.as_ref(),
+            (handle, None) => handle,
+        }
+    }
+
+    pub(crate) fn material_id(&self, entity: Entity) -> Option<AssetId<StandardMaterial>> {
+        self.handle(entity).map(Handle::id)
+    }
+
+    pub(crate) fn color_source(&self, entity: Entity, mesh_key: u64) -> VertexColorSource<'_> {
+        let material = self
+            .handle(entity)
+            .and_then(|handle| self.materials.get(handle));
+        VertexColorSource {
+            mesh_key,
+            base_color: material.map(|material| material.base_color.to_linear().to_vec4()),
+            base_color_texture: material
+                .and_then(|material| material.base_color_texture.as_ref())
+                .and_then(|texture| self.images.get(texture)),
+            ..default()
+        }
+    }
+}
+
+pub trait ColorVertices {
+    fn color_vertices(&mut self, strategy: &VertexColorStrategy, source: &VertexColorSource);
+}
+
+impl ColorVertices for Mesh {
+    fn color_vertices(&mut self, strategy: &VertexColorStrategy, source: &VertexColorSource) {
+        color_vertices(self, strategy, source);
+    }
+}
+
+fn color_vertices(mesh: &mut Mesh, strategy: &VertexColorStrategy, source: &VertexColorSource) {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        warn!("Can't color a mesh without Float32x3 positions");
+        return;
+    };
+
+    let colors: Vec<[f32; 4]> = match strategy {
+        VertexColorStrategy::Random { seed } => {
+            random_colors(positions, *seed, source.weld_tolerance)
+        }
+        VertexColorStrategy::AxisGradient { axis, from, to } => {
+            axis_gradient(positions, *axis, *from, *to)
+        }
+        VertexColorStrategy::Height { low, high } => axis_gradient(positions, Vec3::Y, *low, *high),
+        VertexColorStrategy::Normal => match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float32x3(normals)) => normals
+                .iter()
+                .map(|normal| (Vec3::from(*normal) * 0.5 + 0.5).extend(1.0).to_array())
+                .collect(),
+            _ => {
+                warn!("Mesh has no normals to color by, using white");
+                vec![[1.0; 4]; positions.len()]
+            }
+        },
+        VertexColorStrategy::PerMesh { seed } => {
+            let color = random_color(&mut StdRng::seed_from_u64(seed ^ source.mesh_key));
+            vec![color; positions.len()]
+        }
+        VertexColorStrategy::PerPart { seed } => {
+            part_colors(mesh, positions, *seed, source.weld_tolerance)
+        }
+        VertexColorStrategy::Texture => texture_colors(mesh, positions.len(), source),
+        VertexColorStrategy::KeepOriginal => match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+            Some(VertexAttributeValues::Float32x4(_)) => return,
+            _ => vec![[1.0; 4]; positions.len()],
+        },
+        VertexColorStrategy::Constant(color) => vec![color.to_array(); positions.len()],
+    };
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
+
+fn random_color(rng: &mut StdRng) -> [f32; 4] {
+    [rng.gen::<f32>(), rng.gen::<f32>(), rng.gen::<f32>(), 1.0]
+}
+
+fn random_colors(positions: &[[f32; 3]], seed: u64, weld_tolerance: f32) -> Vec<[f32; 4]> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let weld = weld_positions(positions, weld_tolerance);
+    let colors: Vec<[f32; 4]> = (0..weld.len()).map(|_| random_color(&mut rng)).collect();
+
+    weld.ids.iter().map(|&id| colors[id as usize]).collect()
+}
+
+fn axis_gradient(positions: &[[f32; 3]], axis: Vec3, from: Vec4, to: Vec4) -> Vec<[f32; 4]> {
+    let axis = axis.normalize_or_zero();
+    let along: Vec<f32> = positions
+        .iter()
+        .map(|position| Vec3::from(*position).dot(axis))
+        .collect();
+    let min = along.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = along.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let range = max - min;
+
+    along
+        .iter()
+        .map(|value| {
+            let t = if range > f32::EPSILON {
+                (value - min) / range
+            } else {
+                0.0
+            };
+            from.lerp(to, t).to_array()
+        })
+        .collect()
+}
+
+/// Colors each set of triangles connected through shared positions in its own color.
+fn part_colors(
+    mesh: &Mesh,
+    positions: &[[f32; 3]],
+    seed: u64,
+    weld_tolerance: f32,
+) -> Vec<[f32; 4]> {
+    let mut parts = UnionFind::new(positions.len());
+
+    // Welded vertices belong to the same part even when the mesh is split along UV or
+    // normal seams
+    let weld = weld_positions(positions, weld_tolerance);
+    for (index, &id) in weld.ids.iter().enumerate() {
+        parts.union(weld.remap[id as usize], index);
+    }
+
+    match mesh.indices() {
+        Some(indices) => {
+            let indices: Vec<usize> = indices.iter().collect();
+            for triangle in indices.chunks_exact(3) {
+                parts.union(triangle[0], triangle[1]);
+                parts.union(triangle[1], triangle[2]);
+            }
+        }
+        None => {
+            for triangle in (0..positions.len()).collect::<Vec<_>>().chunks_exact(3) {
+                parts.union(triangle[0], triangle[1]);
+                parts.union(triangle[1], triangle[2]);
+            }
+        }
+    }
+
+    let mut part_colors: HashMap<usize, [f32; 4]> = HashMap::new();
+    (0..positions.len())
+        .map(|index| {
+            let part = parts.find(index);
+            *part_colors
+                .entry(part)
+                .or_insert_with(|| random_color(&mut StdRng::seed_from_u64(seed ^ part as u64)))
+        })
+        .collect()
+}
+
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+        index
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[a.max(b)] = a.min(b);
+        }
+    }
+}
+
+fn texture_colors(mesh: &Mesh, vertex_count: usize, source: &VertexColorSource) -> Vec<[f32; 4]> {
+    let base_color = source.base_color.unwrap_or(Vec4::ONE);
+    let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(uvs)) => Some(uvs),
+        _ => None,
+    };
+
+    let (Some(texture), Some(uvs)) = (source.base_color_texture, uvs) else {
+        return vec![base_color.to_array(); vertex_count];
+    };
+    let srgb = match texture.texture_descriptor.format {
+        TextureFormat::Rgba8UnormSrgb => true,
+        TextureFormat::Rgba8Unorm => false,
+        format => {
+            warn!("Can't sample vertex colors from a {:?} texture", format);
+            return vec![base_color.to_array(); vertex_count];
+        }
+    };
+
+    uvs.iter()
+        .map(|uv| {
+            let texel = sample_texture(texture, srgb, Vec2::from(*uv)).unwrap_or(Vec4::ONE);
+            (texel * base_color).to_array()
+        })
+        .collect()
+}
+
+/// Nearest-neighbour sample of an uncompressed 8-bit RGBA texture, in linear color.
+/// Repeats outside of 0..1.
+fn sample_texture(texture: &Image, srgb: bool, uv: Vec2) -> Option<Vec4> {
+    let size = texture.size();
+    if size.x == 0 || size.y == 0 {
+        return None;
+    }
+    let x = ((uv.x.rem_euclid(1.0) * size.x as f32) as u32).min(size.x - 1);
+    let y = ((uv.y.rem_euclid(1.0) * size.y as f32) as u32).min(size.y - 1);
+    let offset = ((y * size.x + x) * 4) as usize;
+    let &[r, g, b, a] = texture.data.get(offset..offset + 4)? else {
+        return None;
+    };
+
+    let color = if srgb {
+        Color::srgba_u8(r, g, b, a)
+    } else {
+        Color::linear_rgba(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            a as f32 / 255.0,
+        )
+    };
+    Some(color.to_linear().to_vec4())
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, PrimitiveTopology, TextureDimension},
+    };
+
+    use super::*;
+
+    fn triangles(positions: Vec<[f32; 3]>) -> Mesh {
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    }
+
+    fn colors(
+        mut mesh: Mesh,
+        strategy: VertexColorStrategy,
+        source: &VertexColorSource,
+    ) -> Vec<[f32; 4]> {
+        mesh.color_vertices(&strategy, source);
+        match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+            Some(VertexAttributeValues::Float32x4(colors)) => colors.clone(),
+            _ => panic!("mesh has no colors"),
+        }
+    }
+
+    #[test]
+    fn random_colors_are_shared_by_welded_vertices() {
+        // Two triangles sharing an edge, each with its own copy of its vertices
+        let mesh = triangles(vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0],
+        ]);
+        let colors = colors(mesh, VertexColorStrategy::Random { seed: 1 }, &default());
+        assert_eq!(colors[1], colors[3]);
+        assert_eq!(colors[2], colors[5]);
+        assert_ne!(colors[0], colors[1]);
+        assert_ne!(colors[1], colors[4]);
+    }
+
+    #[test]
+    fn axis_gradients_run_between_the_extremes() {
+        let mesh = triangles(vec![[-1.0, 5.0, 0.0], [3.0, 0.0, 0.0], [1.0, -5.0, 0.0]]);
+        let strategy = VertexColorStrategy::AxisGradient {
+            axis: Vec3::X * 2.0,
+            from: Vec4::new(1.0, 0.0, 0.0, 1.0),
+            to: Vec4::new(0.0, 0.0, 1.0, 1.0),
+        };
+        assert_eq!(
+            colors(mesh, strategy, &default()),
+            [
+                [1.0, 0.0, 0.0, 1.0],
+                [0.0, 0.0, 1.0, 1.0],
+                [0.5, 0.0, 0.5, 1.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn each_part_gets_one_color() {
+        let mesh = triangles(vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+            // Only touches the first triangle at a corner
+            [1.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [2.0, 0.0, 1.0],
+            // Away from both
+            [5.0, 0.0, 0.0],
+            [6.0, 0.0, 0.0],
+            [5.0, 0.0, 1.0],
+        ]);
+        let colors = colors(mesh, VertexColorStrategy::PerPart { seed: 1 }, &default());
+        assert!(colors[..6].iter().all(|color| *color == colors[0]));
+        assert!(colors[6..].iter().all(|color| *color == colors[6]));
+        assert_ne!(colors[0], colors[6]);
+    }
+
+    #[test]
+    fn original_colors_are_kept() {
+        let original = vec![
+            [0.1, 0.2, 0.3, 1.0],
+            [0.4, 0.5, 0.6, 1.0],
+            [0.7, 0.8, 0.9, 1.0],
+        ];
+        let positions = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+        let mesh = triangles(positions.clone())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, original.clone());
+        assert_eq!(
+            colors(mesh, VertexColorStrategy::KeepOriginal, &default()),
+            original
+        );
+
+        // Meshes without colors of their own are white
+        assert_eq!(
+            colors(
+                triangles(positions),
+                VertexColorStrategy::KeepOriginal,
+                &default()
+            ),
+            [[1.0; 4]; 3]
+        );
+    }
+
+    #[test]
+    fn textures_are_sampled_at_the_vertex_uvs() {
+        #[rustfmt::skip]
+        let texels = vec![
+            255, 0, 0, 255,     0, 0, 255, 255,
+            0, 255, 0, 255,     255, 255, 255, 255,
+        ];
+        let texture = Image::new(
+            Extent3d {
+                width: 2,
+                height: 2,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            texels,
+            TextureFormat::Rgba8Unorm,
+            RenderAssetUsages::default(),
+        );
+        let mesh = triangles(vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]])
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_UV_0,
+                vec![[0.75, 0.25], [0.25, 0.75], [1.75, 1.75]],
+            );
+        let source = VertexColorSource {
+            base_color: Some(Vec4::new(1.0, 0.5, 0.5, 1.0)),
+            base_color_texture: Some(&texture),
+            ..default()
+        };
+        assert_eq!(
+            colors(mesh, VertexColorStrategy::Texture, &source),
+            [
+                [0.0, 0.0, 0.5, 1.0],
+                [0.0, 0.5, 0.0, 1.0],
+                [1.0, 0.5, 0.5, 1.0],
+            ]
+        );
+    }
+}
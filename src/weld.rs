@@ -0,0 +1,143 @@
+use bevy::{math::Vec3, utils::HashMap};
+
+/// Positions closer than this are treated as the same vertex by the mesh operations unless
+/// configured otherwise, see [`crate::WireframeSettings::weld_tolerance`].
+pub const DEFAULT_WELD_TOLERANCE: f32 = 1e-4;
+
+/// Vertices merged by position, see [`weld_positions`].
+#[derive(Debug, Clone, Default)]
+pub struct Weld {
+    /// The canonical vertex ID of each input vertex.
+    pub ids: Vec<u32>,
+    /// The first input vertex of each canonical vertex, indexed by ID.
+    pub remap: Vec<usize>,
+}
+
+impl Weld {
+    /// Number of canonical vertices.
+    pub fn len(&self) -> usize {
+        self.remap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remap.is_empty()
+    }
+
+    pub fn id(&self, vertex: usize) -> u32 {
+        self.ids[vertex]
+    }
+
+    /// The canonical IDs of an edge's vertices, smallest first.
+    pub fn edge_key(&self, v1: usize, v2: usize) -> (u32, u32) {
+        let (a, b) = (self.ids[v1], self.ids[v2]);
+        if a < b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+}
+
+/// Gives every position a canonical vertex ID, shared with every earlier position within
+/// `tolerance` of the first position that got the ID. Positions are bucketed in a spatial
+/// hash with cells `tolerance` wide, so each lookup only checks the 27 cells around it.
+///
+/// A `tolerance` of zero or less only welds identical positions, counting `-0.0` as `0.0`.
+pub fn weld_positions(positions: &[[f32; 3]], tolerance: f32) -> Weld {
+    let mut weld = Weld {
+        ids: Vec::with_capacity(positions.len()),
+        remap: Vec::new(),
+    };
+
+    if tolerance <= 0.0 {
+        let mut exact: HashMap<[u32; 3], u32> = HashMap::new();
+        for (index, position) in positions.iter().enumerate() {
+            // Adding zero turns -0.0 into 0.0, which compares equal but has other bits
+            let key = position.map(|value| (value + 0.0).to_bits());
+            let id = *exact.entry(key).or_insert_with(|| {
+                weld.remap.push(index);
+                (weld.remap.len() - 1) as u32
+            });
+            weld.ids.push(id);
+        }
+        return weld;
+    }
+
+    let tolerance_squared = tolerance * tolerance;
+    let cell_of = |position: Vec3| (position / tolerance).floor().as_ivec3();
+    let mut cells: HashMap<[i32; 3], Vec<u32>> = HashMap::new();
+
+    for (index, position) in positions.iter().enumerate() {
+        let position = Vec3::from(*position);
+        let cell = cell_of(position);
+
+        let mut found = None;
+        'search: for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    let neighbour = [cell.x + x, cell.y + y, cell.z + z];
+                    for &id in cells.get(&neighbour).into_iter().flatten() {
+                        let canonical = Vec3::from(positions[weld.remap[id as usize]]);
+                        if canonical.distance_squared(position) <= tolerance_squared {
+                            found = Some(id);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+
+        let id = found.unwrap_or_else(|| {
+            let id = weld.remap.len() as u32;
+            weld.remap.push(index);
+            cells.entry(cell.to_array()).or_default().push(id);
+            id
+        });
+        weld.ids.push(id);
+    }
+
+    weld
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_straddling_cells_are_welded() {
+        let weld = weld_positions(
+            &[
+                [0.099, 0.0, 0.0],
+                [0.101, 0.0, 0.0],
+                [-0.001, 0.5, 0.5],
+                [0.001, 0.499, 0.501],
+            ],
+            0.1,
+        );
+        assert_eq!(weld.ids, [0, 0, 1, 1]);
+        assert_eq!(weld.remap, [0, 2]);
+    }
+
+    #[test]
+    fn positions_beyond_the_tolerance_stay_apart() {
+        let weld = weld_positions(&[[0.0, 0.0, 0.0], [0.15, 0.0, 0.0], [0.0, 0.0, -0.15]], 0.1);
+        assert_eq!(weld.ids, [0, 1, 2]);
+        assert_eq!(weld.edge_key(2, 0), (0, 2));
+    }
+
+    #[test]
+    fn zero_tolerance_only_welds_identical_positions() {
+        let weld = weld_positions(
+            &[
+                [1.0, 2.0, 3.0],
+                [1.0, 2.0, 3.000001],
+                [1.0, 2.0, 3.0],
+                [0.0, -0.0, 0.0],
+                [-0.0, 0.0, 0.0],
+            ],
+            0.0,
+        );
+        assert_eq!(weld.ids, [0, 1, 0, 2, 2]);
+        assert_eq!(weld.len(), 3);
+    }
+}
@@ -0,0 +1,1328 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    iter,
+};
+
+use bevy::{
+    asset::LoadState,
+    ecs::system::SystemParam,
+    gltf::GltfPlugin,
+    prelude::*,
+    render::{
+        mesh::{
+            morph::{inherit_weights, MeshMorphWeights},
+            skinning::SkinnedMesh,
+            MeshVertexAttribute,
+        },
+        render_asset::RenderAssetUsages,
+        render_resource::{PrimitiveTopology, VertexFormat},
+    },
+    scene::{SceneInstance, SceneInstanceReady},
+    tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task},
+};
+
+use crate::{
+    fill_material::FillMaterial,
+    gltf_extras::WireframeExtrasQuery,
+    line_material::{
+        LineMaterial, ATTRIBUTE_FACE_NORMAL_A, ATTRIBUTE_FACE_NORMAL_B, ATTRIBUTE_LINE_DISTANCE,
+        ATTRIBUTE_LINE_EDGE_CLASS, ATTRIBUTE_LINE_REVEAL, DASH_SOLID, DEPTH_TEST_ALWAYS,
+        DEPTH_TEST_OCCLUDED, DEPTH_TEST_VISIBLE, LINE_CAP_ROUND, SILHOUETTE_OFF,
+    },
+    load_json::{sidecar_path, JsonLineList, JsonLineListLoader},
+    mesh_cache::{invalidate_mesh_cache, WireframeMeshCache, WireframeMeshKey, WireframeMeshes},
+    mesh_ops::{
+        boundary_edges, check_convertible, line_source_vertices, mark_material_boundaries,
+        mesh_to_wireframe, morph_targets, remap_morph_targets, EdgeMode, LineList, RevealOrder,
+        SmoothNormalsNonIndexed, EDGE_CLASS_COUNT,
+    },
+    outline_material::OutlineMaterial,
+    render_style::{apply_render_styles, HiddenLinesLayer, RenderStyleAssets, XRayLinesLayer},
+    reveal::{advance_reveals, apply_reveals},
+    vertex_colors::{ColorVertices, OriginalMaterials, VertexColorSource, VertexColorStrategy},
+    weld::DEFAULT_WELD_TOLERANCE,
+};
+
+/// Custom vertex attribute written by `blender/assign_unique_indices.py`, used to match
+/// custom edge lists against the vertices of an imported mesh.
+pub const ATTRIBUTE_INDEX: MeshVertexAttribute =
+    MeshVertexAttribute::new("Index", 1237464976, VertexFormat::Float32);
+
+/// Name of the glTF attribute that is imported as [`ATTRIBUTE_INDEX`].
+pub const GLTF_INDEX_ATTRIBUTE: &str = "INDEX";
+
+/// Custom vertex attribute with the material of each triangle, for meshes merged from parts
+/// with different materials. Edges between triangles of different materials become
+/// [`crate::mesh_ops::EdgeClass::MaterialBoundary`] lines. glTF gives each material its own
+/// primitive, so meshes without the attribute have none.
+pub const ATTRIBUTE_MATERIAL_ID: MeshVertexAttribute =
+    MeshVertexAttribute::new("MaterialId", 1237464977, VertexFormat::Float32);
+
+/// Name of the glTF attribute that is imported as [`ATTRIBUTE_MATERIAL_ID`].
+pub const GLTF_MATERIAL_ID_ATTRIBUTE: &str = "MATERIAL_ID";
+
+/// The glTF attributes of line primitives written by `bevy_wireframe-bake`, and the
+/// [`LineMaterial`] attributes they're imported as.
+pub const GLTF_LINE_ATTRIBUTES: [(&str, MeshVertexAttribute); 5] = [
+    ("FACE_NORMAL_A", ATTRIBUTE_FACE_NORMAL_A),
+    ("FACE_NORMAL_B", ATTRIBUTE_FACE_NORMAL_B),
+    ("LINE_DISTANCE", ATTRIBUTE_LINE_DISTANCE),
+    ("LINE_REVEAL", ATTRIBUTE_LINE_REVEAL),
+    ("LINE_EDGE_CLASS", ATTRIBUTE_LINE_EDGE_CLASS),
+];
+
+/// Lines hidden behind the model are not drawn.
+pub const HIDDEN_LINES_HIDE: i32 = 0;
+/// Lines hidden behind the model are drawn like visible ones.
+pub const HIDDEN_LINES_XRAY: i32 = 1;
+/// Lines hidden behind the model are drawn in [`WireframeSettings::line_hidden_color`].
+pub const HIDDEN_LINES_DIM: i32 = 2;
+
+/// Material values shared by every converted scene. A scene's [`WireframeSettings`] override
+/// them where its field of the same layer is `Some`.
+#[derive(Resource, Clone)]
+pub struct ShaderSettings {
+    pub outline_width: f32,
+    pub wireframe_displacement: f32,
+    pub fill_displacement: f32,
+    pub fill_shininess: f32,
+    pub fill_specular_strength: f32,
+    /// View distance at which lines and outlines start fading into `fog_color`.
+    pub fog_near: f32,
+    /// View distance at which the fade is complete. Fog is off unless beyond `fog_near`.
+    pub fog_far: f32,
+    /// The alpha is how much of the fog color distant lines take on.
+    pub fog_color: Vec4,
+    /// HDR multipliers of each layer's color, values above 1.0 bloom.
+    pub line_intensity: f32,
+    pub outline_intensity: f32,
+    pub fill_intensity: f32,
+}
+
+impl Default for ShaderSettings {
+    fn default() -> Self {
+        Self {
+            outline_width: 0.1,
+            wireframe_displacement: 1.5,
+            fill_displacement: 0.0,
+            fill_shininess: 200.0,
+            fill_specular_strength: 1.0,
+            fog_near: 0.0,
+            fog_far: 0.0,
+            fog_color: Vec4::new(0.0, 0.0, 0.0, 1.0),
+            line_intensity: 1.0,
+            outline_intensity: 1.0,
+            fill_intensity: 1.0,
+        }
+    }
+}
+
+impl ShaderSettings {
+    fn apply_to_fill(&self, material: &mut FillMaterial, settings: &WireframeSettings) {
+        material.displacement = self.fill_displacement;
+        material.shininess = settings.fill_shininess.unwrap_or(self.fill_shininess);
+        material.specular_strength = settings
+            .fill_specular_strength
+            .unwrap_or(self.fill_specular_strength);
+        material.intensity = settings.fill_intensity.unwrap_or(self.fill_intensity);
+    }
+
+    fn apply_to_outline(&self, material: &mut OutlineMaterial, settings: &WireframeSettings) {
+        material.outline_width = settings.outline_width.unwrap_or(self.outline_width);
+        material.fog_near = self.fog_near;
+        material.fog_far = self.fog_far;
+        material.fog_color = self.fog_color;
+        material.intensity = settings.outline_intensity.unwrap_or(self.outline_intensity);
+    }
+
+    /// The dimmed lines of [`HIDDEN_LINES_DIM`] keep their own intensity.
+    fn apply_to_lines(
+        &self,
+        material: &mut LineMaterial,
+        settings: &WireframeSettings,
+        hidden: bool,
+    ) {
+        material.displacement = settings
+            .line_displacement
+            .unwrap_or(self.wireframe_displacement);
+        material.fog_near = self.fog_near;
+        material.fog_far = self.fog_far;
+        material.fog_color = self.fog_color;
+        if !hidden {
+            material.intensity = settings.line_intensity.unwrap_or(self.line_intensity);
+        }
+    }
+}
+
+/// Marks a mesh entity, or an ancestor of mesh entities such as a scene root, whose meshes
+/// should be converted to the wireframe look, and describes how they're styled. Scenes are
+/// converted once their instance is ready.
+#[derive(Component, Clone, Debug)]
+pub struct WireframeSettings {
+    /// Which mesh edges become lines.
+    pub edge_mode: EdgeMode,
+    /// How the vertex colors of the line and outline layers are chosen.
+    pub vertex_colors: VertexColorStrategy,
+    /// Vertices closer than this are treated as one when finding edges, smoothing normals
+    /// and coloring. Zero only merges vertices at exactly the same position.
+    pub weld_tolerance: f32,
+    /// Load a [`JsonLineList`] from the `.lines.json` file next to the glTF and use it
+    /// instead of `edge_mode` for meshes with the `INDEX` attribute. Off by default, scenes
+    /// without the file log a load error and fall back to `edge_mode` once it has failed.
+    pub sidecar_edge_list: bool,
+    /// Color of the line layer. `None` keeps the per-vertex colors.
+    pub line_color: Option<Vec4>,
+    /// `None` uses [`ShaderSettings::wireframe_displacement`].
+    pub line_displacement: Option<f32>,
+    /// HDR multiplier of the line color, values above 1.0 bloom. `None` uses
+    /// [`ShaderSettings::line_intensity`].
+    pub line_intensity: Option<f32>,
+    /// One of the `SILHOUETTE_*` modes of [`LineMaterial`].
+    pub line_silhouette_mode: i32,
+    /// Width of the lines. `0.0` draws one pixel wide hardware lines, anything wider
+    /// expands each line into a camera-facing quad.
+    pub line_width: f32,
+    /// Measure `line_width` in world units instead of pixels.
+    pub line_width_in_world_units: bool,
+    /// One of the `LINE_CAP_*` styles of [`LineMaterial`], for lines wider than a pixel.
+    pub line_cap: i32,
+    /// One of the `DASH_*` patterns of [`LineMaterial`].
+    pub line_dash_pattern: i32,
+    /// Length of dashes, in the units of the mesh.
+    pub line_dash_length: f32,
+    pub line_gap_length: f32,
+    /// How far the dash pattern moves along the lines per second.
+    pub line_dash_speed: f32,
+    /// One of [`HIDDEN_LINES_HIDE`], [`HIDDEN_LINES_XRAY`] or [`HIDDEN_LINES_DIM`].
+    pub line_hidden_mode: i32,
+    /// Color and alpha of hidden lines with [`HIDDEN_LINES_DIM`].
+    pub line_hidden_color: Vec4,
+    /// Dash pattern of hidden lines with [`HIDDEN_LINES_DIM`].
+    pub line_hidden_dash_pattern: i32,
+    /// Multiplies the color of the lines of each [`crate::mesh_ops::EdgeClass`], indexed by
+    /// the class. An alpha of 0.0 hides the class, see [`LineMaterial::edge_class_colors`].
+    pub line_edge_class_colors: [Vec4; EDGE_CLASS_COUNT],
+    /// The order lines are drawn on in by a [`crate::reveal::WireframeReveal`].
+    pub reveal_order: RevealOrder,
+    pub fill_color: Vec4,
+    /// The fill and outline values below fall back to the [`ShaderSettings`] field of the
+    /// same name when `None`.
+    pub fill_shininess: Option<f32>,
+    pub fill_specular_strength: Option<f32>,
+    pub fill_intensity: Option<f32>,
+    /// Color of the outline layer. `None` keeps the per-vertex colors.
+    pub outline_color: Option<Vec4>,
+    pub outline_width: Option<f32>,
+    pub outline_intensity: Option<f32>,
+    pub layers: WireframeLayers,
+}
+
+impl Default for WireframeSettings {
+    fn default() -> Self {
+        Self {
+            edge_mode: EdgeMode::All,
+            vertex_colors: VertexColorStrategy::default(),
+            weld_tolerance: DEFAULT_WELD_TOLERANCE,
+            sidecar_edge_list: false,
+            line_color: None,
+            line_displacement: None,
+            line_intensity: None,
+            line_silhouette_mode: SILHOUETTE_OFF,
+            line_width: 0.0,
+            line_width_in_world_units: false,
+            line_cap: LINE_CAP_ROUND,
+            line_dash_pattern: DASH_SOLID,
+            line_dash_length: 0.1,
+            line_gap_length: 0.05,
+            line_dash_speed: 0.0,
+            line_hidden_mode: HIDDEN_LINES_HIDE,
+            line_hidden_color: Vec4::new(0.5, 0.5, 0.5, 0.35),
+            line_hidden_dash_pattern: DASH_SOLID,
+            line_edge_class_colors: [Vec4::ONE; EDGE_CLASS_COUNT],
+            reveal_order: RevealOrder::Index,
+            fill_color: Vec4::new(0.0, 0.0, 0.0, 1.0),
+            fill_shininess: None,
+            fill_specular_strength: None,
+            fill_intensity: None,
+            outline_color: None,
+            outline_width: None,
+            outline_intensity: None,
+            layers: WireframeLayers::default(),
+        }
+    }
+}
+
+/// Which of the generated layers are rendered for a scene.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WireframeLayers {
+    pub line: bool,
+    pub fill: bool,
+    pub outline: bool,
+}
+
+impl Default for WireframeLayers {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl WireframeLayers {
+    pub const ALL: Self = Self {
+        line: true,
+        fill: true,
+        outline: true,
+    };
+
+    pub const LINE_ONLY: Self = Self {
+        line: true,
+        fill: false,
+        outline: false,
+    };
+}
+
+/// The sidecar edge list requested for a scene root, see
+/// [`WireframeSettings::sidecar_edge_list`].
+#[derive(Component, Clone)]
+pub struct WireframeEdgeList(pub Handle<JsonLineList>);
+
+/// Added to a generated line entity that was built from a custom edge list, so it can be
+/// rebuilt when the edge list is hot-reloaded.
+#[derive(Component, Clone)]
+pub struct WireframeEdgeListSource {
+    /// The source mesh before line conversion. Kept in the main world only.
+    pub mesh: Handle<Mesh>,
+    pub edge_list: Handle<JsonLineList>,
+    /// What the mesh's section of the edge list is matched by, see
+    /// [`JsonLineList::line_list_for`].
+    pub mesh_names: Vec<String>,
+    pub gltf_primitive_index: Option<u32>,
+    /// The other primitives of the mesh with a different material, whose boundaries the
+    /// [`crate::mesh_ops::EdgeClass::MaterialBoundary`] lines run along.
+    pub material_seams: Vec<Handle<Mesh>>,
+}
+
+/// Added to the line entities generated for a scene, pointing at the scene root or other
+/// entity carrying its [`WireframeSettings`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct WireframeScene(pub Entity);
+
+/// Added to the line entities generated for a mesh, pointing at the mesh entity they're
+/// children of. They inherit its transform and visibility, follow its [`MeshMorphWeights`]
+/// and are despawned with it.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct WireframeLayerOf(pub Entity);
+
+/// Registers the wireframe materials and the systems that convert marked scenes.
+///
+/// ```ignore
+/// App::new()
+///     .add_plugins(DefaultPlugins.set(WireframePlugin::gltf_plugin()))
+///     .add_plugins(WireframePlugin::new().with_outline_width(0.2))
+/// ```
+#[derive(Default, Clone)]
+pub struct WireframePlugin {
+    shader_settings: ShaderSettings,
+}
+
+impl WireframePlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Initial value of the [`ShaderSettings`] resource.
+    pub fn with_shader_settings(mut self, shader_settings: ShaderSettings) -> Self {
+        self.shader_settings = shader_settings;
+        self
+    }
+
+    pub fn with_outline_width(mut self, outline_width: f32) -> Self {
+        self.shader_settings.outline_width = outline_width;
+        self
+    }
+
+    pub fn with_wireframe_displacement(mut self, displacement: f32) -> Self {
+        self.shader_settings.wireframe_displacement = displacement;
+        self
+    }
+
+    pub fn with_fill_displacement(mut self, displacement: f32) -> Self {
+        self.shader_settings.fill_displacement = displacement;
+        self
+    }
+
+    pub fn with_fill_shininess(mut self, shininess: f32) -> Self {
+        self.shader_settings.fill_shininess = shininess;
+        self
+    }
+
+    pub fn with_fill_specular_strength(mut self, specular_strength: f32) -> Self {
+        self.shader_settings.fill_specular_strength = specular_strength;
+        self
+    }
+
+    pub fn with_line_intensity(mut self, intensity: f32) -> Self {
+        self.shader_settings.line_intensity = intensity;
+        self
+    }
+
+    pub fn with_outline_intensity(mut self, intensity: f32) -> Self {
+        self.shader_settings.outline_intensity = intensity;
+        self
+    }
+
+    pub fn with_fill_intensity(mut self, intensity: f32) -> Self {
+        self.shader_settings.fill_intensity = intensity;
+        self
+    }
+
+    /// Fade lines and outlines into `color` between `near` and `far` from the camera.
+    pub fn with_fog(mut self, near: f32, far: f32, color: Vec4) -> Self {
+        self.shader_settings.fog_near = near;
+        self.shader_settings.fog_far = far;
+        self.shader_settings.fog_color = color;
+        self
+    }
+
+    /// A [`GltfPlugin`] that imports the `INDEX` attribute as [`ATTRIBUTE_INDEX`],
+    /// `MATERIAL_ID` as [`ATTRIBUTE_MATERIAL_ID`], and the [`GLTF_LINE_ATTRIBUTES`] of baked
+    /// scenes.
+    ///
+    /// The glTF loader is created when `GltfPlugin` finishes, so when using `DefaultPlugins`
+    /// it has to be swapped in with `DefaultPlugins.set(WireframePlugin::gltf_plugin())`.
+    /// If no `GltfPlugin` has been added yet, [`WireframePlugin`] adds this one itself.
+    pub fn gltf_plugin() -> GltfPlugin {
+        let mut plugin = GltfPlugin::default()
+            .add_custom_vertex_attribute(GLTF_INDEX_ATTRIBUTE, ATTRIBUTE_INDEX)
+            .add_custom_vertex_attribute(GLTF_MATERIAL_ID_ATTRIBUTE, ATTRIBUTE_MATERIAL_ID);
+        for (name, attribute) in GLTF_LINE_ATTRIBUTES {
+            plugin = plugin.add_custom_vertex_attribute(name, attribute);
+        }
+        plugin
+    }
+}
+
+impl Plugin for WireframePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<GltfPlugin>() {
+            app.add_plugins(Self::gltf_plugin());
+        }
+
+        app.insert_resource(self.shader_settings.clone())
+            .add_plugins(MaterialPlugin::<FillMaterial>::default())
+            .add_plugins(MaterialPlugin::<OutlineMaterial>::default())
+            .add_plugins(MaterialPlugin::<LineMaterial>::default())
+            .init_resource::<WireframeProgress>()
+            .init_resource::<WireframeMeshCache>()
+            .init_resource::<ConversionTasks>()
+            .init_asset::<JsonLineList>()
+            .init_asset_loader::<JsonLineListLoader>()
+            .add_systems(
+                Update,
+                (
+                    load_edge_lists,
+                    invalidate_mesh_cache,
+                    process_scene,
+                    reconvert_modified_meshes,
+                    finish_conversions,
+                    apply_shader_settings,
+                    apply_render_styles,
+                    reload_edge_lists,
+                    advance_reveals,
+                    apply_reveals,
+                )
+                    .chain(),
+            )
+            .add_systems(PostUpdate, mirror_morph_weights.after(inherit_weights));
+    }
+}
+
+fn load_edge_lists(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    roots: Query<(Entity, &WireframeSettings), Added<WireframeSettings>>,
+    scenes: Query<&Handle<Scene>>,
+    meshes: Query<&Handle<Mesh>>,
+) {
+    for (entity, settings) in &roots {
+        if !settings.sidecar_edge_list {
+            continue;
+        }
+        // Meshes loaded on their own from a glTF use its sidecar too
+        let path = match (scenes.get(entity), meshes.get(entity)) {
+            (Ok(scene), _) => scene.path(),
+            (_, Ok(mesh)) => mesh.path(),
+            _ => None,
+        };
+        if let Some(path) = path {
+            let edge_list = asset_server.load::<JsonLineList>(sidecar_path(path));
+            commands.entity(entity).insert(WireframeEdgeList(edge_list));
+        }
+    }
+}
+
+/// Progress of the scene conversions running in the background, for loading screens.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct WireframeProgress {
+    /// Meshes converted since the last time nothing was being converted.
+    pub converted: usize,
+    /// Meshes still being converted.
+    pub pending: usize,
+}
+
+impl WireframeProgress {
+    /// Fraction of the current batch of meshes that has been converted, `1.0` when idle.
+    pub fn fraction(&self) -> f32 {
+        let total = self.converted + self.pending;
+        if total == 0 {
+            1.0
+        } else {
+            self.converted as f32 / total as f32
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending == 0
+    }
+
+    fn start_conversion(&mut self) {
+        // A new batch starts counting from zero
+        if self.is_done() {
+            self.converted = 0;
+        }
+        self.pending += 1;
+    }
+}
+
+/// The meshes built from one source mesh by [`convert_mesh`].
+pub(crate) struct ConvertedMesh {
+    /// Replaces the original mesh, for the fill and outline layers.
+    pub(crate) flat_mesh: Mesh,
+    pub(crate) line_mesh: Option<Mesh>,
+    /// The mesh before line conversion, for [`WireframeEdgeListSource::mesh`].
+    pub(crate) source_mesh: Option<Mesh>,
+    /// Morph targets remapped onto the vertices of `flat_mesh` and `line_mesh`.
+    pub(crate) flat_morph_targets: Option<Image>,
+    pub(crate) line_morph_targets: Option<Image>,
+}
+
+/// Conversions running on the [`AsyncComputeTaskPool`], and the cached handles their meshes
+/// are added under.
+#[derive(Resource, Default)]
+struct ConversionTasks(Vec<(Task<ConvertedMesh>, WireframeMeshes)>);
+
+/// A mesh entity waiting for its [`WireframeMeshes`], and what's needed to attach them once
+/// they're ready.
+#[derive(Component)]
+pub(crate) struct WireframeConversion {
+    pub(crate) meshes: WireframeMeshes,
+    pub(crate) scene: Entity,
+    pub(crate) settings: WireframeSettings,
+    pub(crate) edge_list_source: Option<WireframeEdgeListSource>,
+}
+
+/// Colors the mesh and builds the flat shaded and line meshes from it. Runs off the main
+/// thread, since welding and edge extraction take a while on big meshes.
+pub(crate) fn convert_mesh(
+    mut flat_mesh: Mesh,
+    settings: &WireframeSettings,
+    color_source: &VertexColorSource,
+    edge_list: Option<&[[u32; 2]]>,
+    keep_source: bool,
+    morph_targets: Option<&Image>,
+    material_seams: &[[Vec3; 2]],
+) -> ConvertedMesh {
+    flat_mesh.color_vertices(&settings.vertex_colors, color_source);
+
+    let mut smooth_mesh = flat_mesh.clone();
+    smooth_mesh.smooth_normals_non_indexed(settings.weld_tolerance);
+    // Unsharing the vertices reorders them, the morph targets have to follow
+    let flat_morph_targets = match (morph_targets, flat_mesh.indices()) {
+        (Some(targets), Some(indices)) => {
+            let source_vertices: Vec<u32> = indices.iter().map(|index| index as u32).collect();
+            remap_morph_targets(targets, &source_vertices)
+                .map_err(|e| warn!("Dropping morph targets: {}", e))
+                .ok()
+        }
+        _ => None,
+    };
+    flat_mesh.duplicate_vertices();
+    flat_mesh.compute_flat_normals();
+
+    if !settings.layers.line {
+        return ConvertedMesh {
+            flat_mesh,
+            line_mesh: None,
+            source_mesh: None,
+            flat_morph_targets,
+            line_morph_targets: None,
+        };
+    }
+
+    let source_mesh = keep_source.then(|| {
+        let mut source_mesh = smooth_mesh.clone();
+        source_mesh.asset_usage = RenderAssetUsages::MAIN_WORLD;
+        source_mesh
+    });
+
+    // Without lines the mesh is left as it was, it mustn't be drawn as one
+    let (line_mesh, line_morph_targets) = match build_line_mesh(
+        &mut smooth_mesh,
+        settings,
+        edge_list,
+        material_seams,
+        morph_targets,
+    ) {
+        Ok(line_morph_targets) => (Some(smooth_mesh), line_morph_targets),
+        Err(e) => {
+            warn!("Error: {:?}", e);
+            (None, None)
+        }
+    };
+
+    ConvertedMesh {
+        flat_mesh,
+        line_mesh,
+        source_mesh,
+        flat_morph_targets,
+        line_morph_targets,
+    }
+}
+
+/// Replaces `mesh` with its line mesh, like [`mesh_to_wireframe`], and marks the lines along
+/// `material_seams`. Returns the line mesh's morph targets, remapped from the `morph_targets`
+/// of `mesh`, whose names the line mesh takes over.
+fn build_line_mesh(
+    mesh: &mut Mesh,
+    settings: &WireframeSettings,
+    edge_list: Option<&[[u32; 2]]>,
+    material_seams: &[[Vec3; 2]],
+    morph_targets: Option<&Image>,
+) -> anyhow::Result<Option<Image>> {
+    let morph_target_names = mesh.morph_target_names().map(<[String]>::to_vec);
+    let mut line_list = mesh_to_wireframe(mesh, settings, edge_list)?;
+    mark_material_boundaries(
+        &mut line_list,
+        mesh,
+        material_seams,
+        settings.weld_tolerance,
+    );
+
+    let line_morph_targets =
+        morph_targets.and_then(|targets| line_morph_targets(&line_list, mesh, targets));
+    if let (Some(_), Some(names)) = (&line_morph_targets, morph_target_names) {
+        mesh.set_morph_target_names(names);
+    }
+    Ok(line_morph_targets)
+}
+
+/// Morph targets for a line mesh built from `line_list`, remapped from the `targets` of the
+/// mesh the lines were extracted from.
+fn line_morph_targets(line_list: &LineList, line_mesh: &Mesh, targets: &Image) -> Option<Image> {
+    let thick = line_mesh.primitive_topology() == PrimitiveTopology::TriangleList;
+    let source_vertices = line_source_vertices(line_list, thick)?;
+    remap_morph_targets(targets, &source_vertices)
+        .map_err(|e| warn!("Dropping morph targets: {}", e))
+        .ok()
+}
+
+/// Everything needed to start converting a mesh entity, shared by the conversion of newly
+/// spawned scenes and of modified meshes.
+#[derive(SystemParam)]
+struct MeshConverter<'w, 's> {
+    commands: Commands<'w, 's>,
+    mesh_assets: Res<'w, Assets<Mesh>>,
+    images: Res<'w, Assets<Image>>,
+    mesh_cache: ResMut<'w, WireframeMeshCache>,
+    tasks: ResMut<'w, ConversionTasks>,
+    gltf_extras: WireframeExtrasQuery<'w, 's>,
+    edge_lists: Query<'w, 's, &'static WireframeEdgeList>,
+    edge_list_assets: Res<'w, Assets<JsonLineList>>,
+    original_materials: OriginalMaterials<'w, 's>,
+    progress: ResMut<'w, WireframeProgress>,
+    parents: Query<'w, 's, &'static Parent>,
+    children: Query<'w, 's, &'static Children>,
+    source_meshes: Query<
+        'w,
+        's,
+        (&'static Handle<Mesh>, Option<&'static RenderStyleAssets>),
+        Without<WireframeLayerOf>,
+    >,
+}
+
+impl MeshConverter<'_, '_> {
+    /// Whether the sidecar edge list of the scene `parent` has either loaded or failed to
+    /// load, scenes wait for it before converting.
+    fn edge_list_ready(&self, parent: Entity, asset_server: &AssetServer) -> bool {
+        self.edge_lists.get(parent).map_or(true, |edge_list| {
+            matches!(
+                asset_server.load_state(&edge_list.0),
+                LoadState::Loaded | LoadState::Failed(_)
+            )
+        })
+    }
+
+    /// The unconverted meshes of the siblings of `entity` with a different material. glTF
+    /// meshes are spawned as one child per primitive, and a primitive per material.
+    fn material_seams(&self, entity: Entity) -> Vec<Handle<Mesh>> {
+        let Ok(parent) = self.parents.get(entity) else {
+            return vec![];
+        };
+        let material = self.original_materials.material_id(entity);
+        self.children
+            .get(parent.get())
+            .into_iter()
+            .flatten()
+            .filter(|&&sibling| {
+                sibling != entity && self.original_materials.material_id(sibling) != material
+            })
+            .filter_map(|&sibling| {
+                let (mesh, assets) = self.source_meshes.get(sibling).ok()?;
+                Some(assets.map_or(mesh, |assets| &assets.original_mesh).clone())
+            })
+            .collect()
+    }
+
+    /// Starts converting `mesh_handle`, the unconverted mesh of `entity`, with the
+    /// `settings` of its scene root `parent`.
+    fn start(
+        &mut self,
+        entity: Entity,
+        mesh_handle: &Handle<Mesh>,
+        parent: Entity,
+        settings: &WireframeSettings,
+    ) {
+        let edge_list_handle = self
+            .edge_lists
+            .get(parent)
+            .ok()
+            .map(|edge_list| &edge_list.0);
+        let sidecar = edge_list_handle.and_then(|handle| self.edge_list_assets.get(handle));
+
+        // Custom properties from Blender override the scene's settings per mesh
+        let extras = self.gltf_extras.get(entity, parent);
+        if extras.skip() {
+            return;
+        }
+        let mut wireframe_settings = settings.clone();
+        extras.apply(&mut wireframe_settings);
+
+        // Meshes written by `bevy_wireframe-bake` only need their materials. Their
+        // line primitives become the line layer, the others the fill and outline.
+        if extras.baked() {
+            let lines = self
+                .mesh_assets
+                .get(mesh_handle)
+                .is_some_and(|mesh| mesh.primitive_topology() == PrimitiveTopology::LineList);
+            let layers = &mut wireframe_settings.layers;
+            layers.fill &= !lines;
+            layers.outline &= !lines;
+            layers.line &= lines;
+            let meshes = WireframeMeshes {
+                flat_mesh: mesh_handle.clone(),
+                line_mesh: layers.line.then(|| mesh_handle.clone()),
+                source_mesh: None,
+                morphed: self
+                    .mesh_assets
+                    .get(mesh_handle)
+                    .is_some_and(Mesh::has_morph_targets),
+            };
+
+            self.progress.start_conversion();
+            self.commands.entity(entity).insert(WireframeConversion {
+                meshes,
+                scene: parent,
+                settings: wireframe_settings,
+                edge_list_source: None,
+            });
+            return;
+        }
+
+        // An edge list embedded in the extras takes precedence over the sidecar
+        let mesh_names = self.gltf_extras.mesh_names(entity);
+        let sidecar_edge_list = sidecar.and_then(|sidecar| {
+            let names: Vec<&str> = mesh_names.iter().map(String::as_str).collect();
+            sidecar.line_list_for(&names, extras.gltf_primitive_index)
+        });
+        let embedded_edge_list = extras.edge_list();
+        let edge_list = embedded_edge_list.or(sidecar_edge_list);
+
+        // Keep the unconverted mesh around so the lines can be rebuilt when the
+        // edge list is hot-reloaded
+        let keep_source = edge_list_handle.is_some()
+            && sidecar_edge_list.is_some()
+            && embedded_edge_list.is_none()
+            && wireframe_settings.layers.line;
+
+        // Meshes keep their colors across runs as long as their names don't change
+        let mut hasher = DefaultHasher::new();
+        mesh_names.hash(&mut hasher);
+        let mesh_key = hasher.finish();
+
+        let material_seams = match wireframe_settings.layers.line {
+            true => self.material_seams(entity),
+            false => vec![],
+        };
+
+        // Instances of the same mesh share the meshes generated for the first one
+        let cache_key = WireframeMeshKey::new(
+            mesh_handle.id(),
+            &wireframe_settings,
+            mesh_key,
+            self.original_materials.material_id(entity),
+            edge_list,
+            keep_source,
+            &material_seams,
+        );
+        let meshes = match self.mesh_cache.get(&cache_key) {
+            Some(meshes) => meshes,
+            None => {
+                let Some(mesh) = self.mesh_assets.get(mesh_handle).cloned() else {
+                    return;
+                };
+                // Points, lines and odd vertex formats are left as they are
+                if let Err(e) = check_convertible(&mesh) {
+                    warn!("Skipping mesh of {:?}: {}", entity, e);
+                    return;
+                }
+
+                let color_source = self.original_materials.color_source(entity, mesh_key);
+                let base_color = color_source.base_color;
+                // The task needs its own copy of the texture, only made when it's
+                // sampled
+                let base_color_texture = match wireframe_settings.vertex_colors {
+                    VertexColorStrategy::Texture => color_source.base_color_texture.cloned(),
+                    _ => None,
+                };
+                // And of the morph targets, to remap them onto the new vertices
+                let source_morph_targets = morph_targets(&mesh)
+                    .and_then(|handle| self.images.get(handle))
+                    .cloned();
+                // And of the sibling primitives, whose boundaries are only found in the task
+                let seam_meshes: Vec<Mesh> = material_seams
+                    .iter()
+                    .filter_map(|handle| self.mesh_assets.get(handle).cloned())
+                    .collect();
+
+                let meshes = WireframeMeshes {
+                    flat_mesh: self.mesh_assets.reserve_handle(),
+                    line_mesh: wireframe_settings
+                        .layers
+                        .line
+                        .then(|| self.mesh_assets.reserve_handle()),
+                    source_mesh: keep_source.then(|| self.mesh_assets.reserve_handle()),
+                    morphed: source_morph_targets.is_some(),
+                };
+
+                let settings = wireframe_settings.clone();
+                let edge_list = edge_list.map(<[_]>::to_vec);
+                let task = AsyncComputeTaskPool::get().spawn(async move {
+                    let color_source = VertexColorSource {
+                        mesh_key,
+                        base_color,
+                        base_color_texture: base_color_texture.as_ref(),
+                        weld_tolerance: settings.weld_tolerance,
+                    };
+                    let material_seams: Vec<[Vec3; 2]> = seam_meshes
+                        .iter()
+                        .flat_map(|mesh| boundary_edges(mesh, settings.weld_tolerance))
+                        .collect();
+                    convert_mesh(
+                        mesh,
+                        &settings,
+                        &color_source,
+                        edge_list.as_deref(),
+                        keep_source,
+                        source_morph_targets.as_ref(),
+                        &material_seams,
+                    )
+                });
+                self.tasks.0.push((task, meshes.clone()));
+                self.mesh_cache.insert(cache_key, &meshes);
+                meshes
+            }
+        };
+
+        let edge_list_source = match (edge_list_handle, &meshes.source_mesh) {
+            (Some(handle), Some(source_mesh)) => Some(WireframeEdgeListSource {
+                mesh: source_mesh.clone(),
+                edge_list: handle.clone(),
+                mesh_names,
+                gltf_primitive_index: extras.gltf_primitive_index,
+                material_seams,
+            }),
+            _ => None,
+        };
+
+        self.progress.start_conversion();
+        self.commands.entity(entity).insert(WireframeConversion {
+            meshes,
+            scene: parent,
+            settings: wireframe_settings,
+            edge_list_source,
+        });
+    }
+}
+
+/// Converts the meshes of entities given [`WireframeSettings`]: the entity's own mesh and
+/// those of its descendants. Scenes are converted once their instance is spawned. Each mesh
+/// takes the settings of its nearest marked ancestor, and meshes that are already converted
+/// are left alone.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn process_scene(
+    mut events: EventReader<SceneInstanceReady>,
+    added: Query<(Entity, Option<&SceneInstance>, Has<Handle<Scene>>), Added<WireframeSettings>>,
+    children: Query<&Children>,
+    parents: Query<&Parent>,
+    meshes: Query<&Handle<Mesh>, Without<WireframeLayerOf>>,
+    converted: Query<(), Or<(With<RenderStyleAssets>, With<WireframeConversion>)>>,
+    processable_scenes: Query<&WireframeSettings>,
+    scene_spawner: Res<SceneSpawner>,
+    asset_server: Res<AssetServer>,
+    mut converter: MeshConverter,
+    mut pending: Local<Vec<Entity>>,
+) {
+    pending.extend(
+        events
+            .read()
+            .map(|event| event.parent)
+            .filter(|parent| processable_scenes.contains(*parent)),
+    );
+    // Scene roots marked after their instance spawned don't get another ready event
+    pending.extend(
+        added
+            .iter()
+            .filter(|(_, instance, is_scene)| {
+                !is_scene
+                    || instance.is_some_and(|instance| scene_spawner.instance_is_ready(**instance))
+            })
+            .map(|(entity, _, _)| entity),
+    );
+    pending.sort_unstable();
+    pending.dedup();
+
+    let with_descendants =
+        |parent: Entity| iter::once(parent).chain(children.iter_descendants(parent));
+
+    // Roots wait here until their sidecar edge list has either loaded or failed to load,
+    // and until their meshes have loaded
+    let ready: Vec<Entity>;
+    (ready, *pending) = pending.drain(..).partition(|parent| {
+        converter.edge_list_ready(*parent, &asset_server)
+            && meshes
+                .iter_many(with_descendants(*parent))
+                .all(|mesh| converter.mesh_assets.contains(mesh))
+    });
+
+    for parent in ready {
+        let Ok(wireframe_settings) = processable_scenes.get(parent) else {
+            continue;
+        };
+        for entity in with_descendants(parent) {
+            let Ok(mesh_handle) = meshes.get(entity) else {
+                continue;
+            };
+            let nearest = iter::once(entity)
+                .chain(parents.iter_ancestors(entity))
+                .find(|ancestor| processable_scenes.contains(*ancestor));
+            if nearest == Some(parent) && !converted.contains(entity) {
+                converter.start(entity, mesh_handle, parent, wireframe_settings);
+            }
+        }
+    }
+}
+
+/// Converts meshes again when their source mesh asset is modified, starting from the
+/// unconverted mesh so the flat shading isn't applied on top of itself. The previous layers
+/// stay until the new ones are ready.
+fn reconvert_modified_meshes(
+    mut events: EventReader<AssetEvent<Mesh>>,
+    converted: Query<(Entity, &RenderStyleAssets)>,
+    converting: Query<(Entity, &Handle<Mesh>, &WireframeConversion)>,
+    scenes: Query<&WireframeSettings>,
+    mut converter: MeshConverter,
+) {
+    let modified: Vec<AssetId<Mesh>> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if modified.is_empty() {
+        return;
+    }
+
+    let sources: Vec<(Entity, Handle<Mesh>, Entity)> = converted
+        .iter()
+        .map(|(entity, assets)| (entity, assets.original_mesh.clone(), assets.scene))
+        .chain(
+            converting
+                .iter()
+                .map(|(entity, mesh, conversion)| (entity, mesh.clone(), conversion.scene)),
+        )
+        .filter(|(_, mesh, _)| modified.contains(&mesh.id()))
+        .collect();
+
+    for (entity, mesh, parent) in sources {
+        if let Ok(settings) = scenes.get(parent) {
+            converter.start(entity, &mesh, parent, settings);
+        }
+    }
+}
+
+/// Adds the meshes of finished conversion tasks, then swaps them into every
+/// [`WireframeConversion`] waiting on them and spawns the line entities.
+#[allow(clippy::too_many_arguments)]
+fn finish_conversions(
+    mut commands: Commands,
+    mut tasks: ResMut<ConversionTasks>,
+    mut conversions: Query<(
+        Entity,
+        &mut WireframeConversion,
+        Option<&SkinnedMesh>,
+        Option<&MeshMorphWeights>,
+    )>,
+    originals: Query<(&Handle<Mesh>, Option<&Handle<StandardMaterial>>)>,
+    previous: Query<(Option<&RenderStyleAssets>, Option<&Children>)>,
+    layers: Query<(), With<WireframeLayerOf>>,
+    mut mesh_assets: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut line_materials: ResMut<Assets<LineMaterial>>,
+    mut fill_materials: ResMut<Assets<FillMaterial>>,
+    mut outline_materials: ResMut<Assets<OutlineMaterial>>,
+    shader_settings: Res<ShaderSettings>,
+    mut progress: ResMut<WireframeProgress>,
+) {
+    tasks.0.retain_mut(|(task, meshes)| {
+        let Some(converted) = block_on(future::poll_once(task)) else {
+            return true;
+        };
+        let mut flat_mesh = converted.flat_mesh;
+        if let Some(morph_targets) = converted.flat_morph_targets {
+            flat_mesh.set_morph_targets(images.add(morph_targets));
+        }
+        mesh_assets.insert(&meshes.flat_mesh, flat_mesh);
+        if let (Some(handle), Some(mut line_mesh)) = (&meshes.line_mesh, converted.line_mesh) {
+            if let Some(morph_targets) = converted.line_morph_targets {
+                line_mesh.set_morph_targets(images.add(morph_targets));
+            }
+            mesh_assets.insert(handle, line_mesh);
+        }
+        if let (Some(handle), Some(source_mesh)) = (&meshes.source_mesh, converted.source_mesh) {
+            mesh_assets.insert(handle, source_mesh);
+        }
+        false
+    });
+
+    let mut pending = 0;
+
+    for (entity, mut conversion, skinned_mesh, morph_weights) in &mut conversions {
+        if !mesh_assets.contains(&conversion.meshes.flat_mesh) {
+            pending += 1;
+            continue;
+        }
+        let Ok((mut original_mesh, mut original_material)) = originals.get(entity) else {
+            continue;
+        };
+        progress.converted += 1;
+
+        // A reconverted mesh swaps out the layers it was given the first time round
+        if let Ok((previous_assets, children)) = previous.get(entity) {
+            if let Some(previous_assets) = previous_assets {
+                original_mesh = &previous_assets.original_mesh;
+                original_material = previous_assets.original_material.as_ref();
+            }
+            for &child in children.into_iter().flatten() {
+                if layers.contains(child) {
+                    commands.entity(child).despawn_recursive();
+                }
+            }
+        }
+
+        let edge_list_source = conversion.edge_list_source.take();
+        let parent = conversion.scene;
+        let wireframe_settings = &conversion.settings;
+
+        let fill = wireframe_settings.layers.fill.then(|| {
+            let mut fill = FillMaterial {
+                color: wireframe_settings.fill_color,
+                ..default()
+            };
+            shader_settings.apply_to_fill(&mut fill, wireframe_settings);
+            fill_materials.add(fill)
+        });
+
+        let outline = wireframe_settings.layers.outline.then(|| {
+            let mut outline = OutlineMaterial {
+                flat_color: wireframe_settings
+                    .outline_color
+                    .unwrap_or(OutlineMaterial::default().flat_color),
+                use_vertex_color: wireframe_settings.outline_color.is_none() as i32,
+                ..default()
+            };
+            shader_settings.apply_to_outline(&mut outline, wireframe_settings);
+            outline_materials.add(outline)
+        });
+
+        // Baked line primitives have no original look to go back to
+        let original_material = original_material.cloned().filter(|_| {
+            mesh_assets
+                .get(original_mesh)
+                .is_some_and(|mesh| mesh.primitive_topology() != PrimitiveTopology::LineList)
+        });
+
+        // The materials are swapped in by `apply_render_styles`
+        commands
+            .entity(entity)
+            .remove::<WireframeConversion>()
+            .insert(RenderStyleAssets {
+                scene: parent,
+                original_mesh: original_mesh.clone(),
+                original_material,
+                flat_mesh: conversion.meshes.flat_mesh.clone(),
+                fill,
+                outline,
+            })
+            .insert(ConvertedSettings(wireframe_settings.clone()));
+
+        let Some(new_mesh_handle) = conversion.meshes.line_mesh.clone() else {
+            continue;
+        };
+
+        let skinned_mesh = skinned_mesh.cloned();
+        // Line meshes with morph targets follow the weights of the mesh they were built from
+        let morph_weights = morph_weights.filter(|_| conversion.meshes.morphed).cloned();
+
+        let mut line_material = LineMaterial {
+            width: wireframe_settings.line_width,
+            width_in_world_units: wireframe_settings.line_width_in_world_units as i32,
+            line_cap: wireframe_settings.line_cap,
+            dash_pattern: wireframe_settings.line_dash_pattern,
+            dash_length: wireframe_settings.line_dash_length,
+            gap_length: wireframe_settings.line_gap_length,
+            dash_speed: wireframe_settings.line_dash_speed,
+            edge_class_colors: wireframe_settings.line_edge_class_colors,
+            // The hidden pass has to pick the same edges as the visible one
+            silhouette_mode: wireframe_settings.line_silhouette_mode,
+            ..default()
+        };
+        shader_settings.apply_to_lines(&mut line_material, wireframe_settings, false);
+
+        // Hidden lines get their own entity sharing the line mesh, drawn only
+        // where the visible pass is occluded
+        if wireframe_settings.line_hidden_mode == HIDDEN_LINES_DIM {
+            let mut hidden_commands = commands.spawn((
+                MaterialMeshBundle {
+                    mesh: new_mesh_handle.clone(),
+                    material: line_materials.add(LineMaterial {
+                        color: wireframe_settings.line_hidden_color,
+                        use_vertex_color: 0,
+                        // The hidden color is used as is, dimmed lines shouldn't bloom
+                        intensity: 1.0,
+                        dash_pattern: wireframe_settings.line_hidden_dash_pattern,
+                        depth_test: DEPTH_TEST_OCCLUDED,
+                        ..line_material.clone()
+                    }),
+                    ..Default::default()
+                },
+                WireframeScene(parent),
+                WireframeLayerOf(entity),
+                HiddenLinesLayer,
+            ));
+            hidden_commands.set_parent(entity);
+            if let Some(skinned_mesh) = skinned_mesh.clone() {
+                hidden_commands.insert(skinned_mesh);
+            }
+            if let Some(morph_weights) = morph_weights.clone() {
+                hidden_commands.insert(morph_weights);
+            }
+        }
+
+        let bundle = MaterialMeshBundle {
+            mesh: new_mesh_handle,
+            material: line_materials.add(LineMaterial {
+                color: wireframe_settings
+                    .line_color
+                    .unwrap_or(LineMaterial::default().color),
+                use_vertex_color: wireframe_settings.line_color.is_none() as i32,
+                depth_test: if wireframe_settings.line_hidden_mode == HIDDEN_LINES_XRAY {
+                    DEPTH_TEST_ALWAYS
+                } else {
+                    DEPTH_TEST_VISIBLE
+                },
+                ..line_material
+            }),
+            ..Default::default()
+        };
+
+        // Spawn the new entity under the mesh, so static meshes keep their placement
+        let mut entity_commands =
+            commands.spawn((bundle, WireframeScene(parent), WireframeLayerOf(entity)));
+        entity_commands.set_parent(entity);
+
+        // If the original entity had a SkinnedMesh component, add it to the new entity
+        if let Some(skinned_mesh) = skinned_mesh {
+            entity_commands.insert(skinned_mesh);
+        }
+
+        if let Some(morph_weights) = morph_weights {
+            entity_commands.insert(morph_weights);
+        }
+
+        if let Some(edge_list_source) = edge_list_source {
+            entity_commands.insert(edge_list_source);
+        }
+
+        if wireframe_settings.line_hidden_mode == HIDDEN_LINES_XRAY {
+            entity_commands.insert(XRayLinesLayer);
+        }
+    }
+
+    progress.pending = pending;
+}
+
+/// The settings a mesh was converted with, its glTF extras applied. The material values
+/// they leave unset follow [`ShaderSettings`] in [`apply_shader_settings`], and
+/// [`reload_edge_lists`] rebuilds the lines with them.
+#[derive(Component)]
+pub(crate) struct ConvertedSettings(WireframeSettings);
+
+/// Carries changes of [`ShaderSettings`] over to the materials of converted meshes, the
+/// same way [`finish_conversions`] first set them.
+fn apply_shader_settings(
+    shader_settings: Res<ShaderSettings>,
+    meshes: Query<(&RenderStyleAssets, &ConvertedSettings)>,
+    lines: Query<(
+        &WireframeLayerOf,
+        &Handle<LineMaterial>,
+        Has<HiddenLinesLayer>,
+    )>,
+    mut line_materials: ResMut<Assets<LineMaterial>>,
+    mut fill_materials: ResMut<Assets<FillMaterial>>,
+    mut outline_materials: ResMut<Assets<OutlineMaterial>>,
+) {
+    if !shader_settings.is_changed() {
+        return;
+    }
+
+    for (assets, settings) in &meshes {
+        if let Some(fill) = assets
+            .fill
+            .as_ref()
+            .and_then(|fill| fill_materials.get_mut(fill))
+        {
+            shader_settings.apply_to_fill(fill, &settings.0);
+        }
+        if let Some(outline) = assets
+            .outline
+            .as_ref()
+            .and_then(|outline| outline_materials.get_mut(outline))
+        {
+            shader_settings.apply_to_outline(outline, &settings.0);
+        }
+    }
+
+    for (layer_of, material, hidden) in &lines {
+        let Ok((_, settings)) = meshes.get(layer_of.0) else {
+            continue;
+        };
+        if let Some(material) = line_materials.get_mut(material) {
+            shader_settings.apply_to_lines(material, &settings.0, hidden);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn reload_edge_lists(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<JsonLineList>>,
+    line_entities: Query<(
+        Entity,
+        &Handle<Mesh>,
+        &WireframeEdgeListSource,
+        &WireframeLayerOf,
+        Has<MeshMorphWeights>,
+    )>,
+    sources: Query<(&ConvertedSettings, Option<&MeshMorphWeights>)>,
+    edge_list_assets: Res<Assets<JsonLineList>>,
+    mut mesh_assets: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for event in events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        let Some(edge_list) = edge_list_assets.get(*id) else {
+            continue;
+        };
+
+        for (entity, line_mesh_handle, source, layer_of, has_weights) in &line_entities {
+            if source.edge_list.id() != *id {
+                continue;
+            }
+            let names: Vec<&str> = source.mesh_names.iter().map(String::as_str).collect();
+            let Some(edge_list) = edge_list.line_list_for(&names, source.gltf_primitive_index)
+            else {
+                continue;
+            };
+            let Ok((settings, source_weights)) = sources.get(layer_of.0) else {
+                continue;
+            };
+            let Some(mut line_mesh) = mesh_assets.get(&source.mesh).cloned() else {
+                continue;
+            };
+
+            let material_seams: Vec<[Vec3; 2]> = source
+                .material_seams
+                .iter()
+                .filter_map(|handle| mesh_assets.get(handle))
+                .flat_map(|mesh| boundary_edges(mesh, settings.0.weld_tolerance))
+                .collect();
+            let morph_targets = morph_targets(&line_mesh)
+                .and_then(|handle| images.get(handle))
+                .cloned();
+            let line_morph_targets = match build_line_mesh(
+                &mut line_mesh,
+                &settings.0,
+                Some(edge_list),
+                &material_seams,
+                morph_targets.as_ref(),
+            ) {
+                Ok(line_morph_targets) => line_morph_targets,
+                Err(e) => {
+                    warn!("Error: {:?}", e);
+                    continue;
+                }
+            };
+
+            // The new lines may follow the source's morph weights where the old ones didn't
+            let source_weights = source_weights.filter(|_| line_morph_targets.is_some());
+            match (source_weights, has_weights) {
+                (Some(weights), false) => {
+                    commands.entity(entity).insert(weights.clone());
+                }
+                (None, true) => {
+                    commands.entity(entity).remove::<MeshMorphWeights>();
+                }
+                _ => {}
+            }
+            if let Some(morph_targets) = line_morph_targets {
+                line_mesh.set_morph_targets(images.add(morph_targets));
+            }
+            mesh_assets.insert(line_mesh_handle, line_mesh);
+        }
+    }
+}
+
+/// Copies the [`MeshMorphWeights`] of morphed meshes onto their line entities, after Bevy
+/// has set them from the glTF node's [`MorphWeights`].
+fn mirror_morph_weights(
+    mut lines: Query<(&WireframeLayerOf, &mut MeshMorphWeights)>,
+    sources: Query<&MeshMorphWeights, (Changed<MeshMorphWeights>, Without<WireframeLayerOf>)>,
+) {
+    for (source, mut weights) in &mut lines {
+        if let Ok(source_weights) = sources.get(source.0) {
+            weights.clone_from(source_weights);
+        }
+    }
+}